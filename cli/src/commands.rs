@@ -1,8 +1,5 @@
 use crate::register_commands;
 
-use async_trait::async_trait;
-
-#[async_trait]
 pub(crate) trait Command {
     async fn run(&self) -> Result<(), ()>;
 }
@@ -1,7 +1,6 @@
 mod commands;
 mod utils;
 
-use async_trait::async_trait;
 use clap::{Parser, Subcommand};
 
 use crate::commands::Command;
@@ -21,7 +20,6 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {}
 
-#[async_trait]
 impl Command for Commands {
     async fn run(&self) -> Result<(), ()> {
         // match self {}
@@ -11,4 +11,18 @@ app.get("/multi/:id/:name", ({ req, res }) => {
   res.json({ id, name });
 });
 
+// only digits reach this route; `numericParams` has `id` as a number
+app.get("/users/:id<int>", ({ req, res }) => {
+  res.json({ id: req.numericParams.id });
+});
+
+// anything else falls through to the next matching route
+app.get("/users/:name", ({ req, res }) => {
+  res.json({ name: req.params.name });
+});
+
+app.get("/posts/:slug([a-z0-9-]+)", ({ req, res }) => {
+  res.send(req.params.slug);
+});
+
 app.listen(3000);
@@ -1,12 +1,14 @@
-use http_body_util::BodyExt;
+use http_body_util::{BodyExt, LengthLimitError, Limited};
 use hyper::{
-    Request,
-    body::{Bytes, Incoming},
+    body::{Body, Bytes},
+    http::request::Parts,
 };
 
 use napi::bindgen_prelude::{Buffer, External};
 
-use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+use std::{collections::HashMap, error::Error, fmt, sync::Arc, time::Duration};
+
+use crate::server::connection::{ConnectionInfo, PeerCredentialsCore};
 
 #[derive(Clone)]
 pub struct RequestCore {
@@ -21,29 +23,52 @@ pub struct RequestCore {
     pub xhr: bool,
     pub ip: String,
     pub ips: Vec<String>,
+    /// Uid/gid/pid of the client process, for requests received over a Unix socket.
+    pub peer_credentials: Option<PeerCredentialsCore>,
 
     pub body: Bytes,
     pub headers_raw: HashMap<String, String>,
     pub params: HashMap<String, String>,
+    /// Params declared `<int>` or `<float>`, handed to JavaScript as numbers.
+    pub numeric_params: HashMap<String, f64>,
     pub query_raw: HashMap<String, Vec<String>>,
     pub cookies_raw: HashMap<String, String>,
 }
 
+#[derive(Debug)]
+pub enum BodyError {
+    /// The body grew past the effective `max_request_size`.
+    TooLarge,
+    /// The body was not received within `body_read_timeout`.
+    Timeout,
+    Read(Box<dyn Error + Send + Sync>),
+}
+
+impl fmt::Display for BodyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BodyError::TooLarge => write!(f, "request body exceeds the maximum allowed size"),
+            BodyError::Timeout => write!(f, "timed out reading request body"),
+            BodyError::Read(e) => write!(f, "failed to read request body: {e}"),
+        }
+    }
+}
+
+impl Error for BodyError {}
+
 impl RequestCore {
-    pub async fn new(
-        req: Request<Incoming>,
-        remote_addr: Option<SocketAddr>,
-        trust_proxy: bool,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
-        let method = req.method().as_str().to_string();
-        let uri = req.uri();
-        let url = uri.to_string();
+    /// Build the request from its head. The body stays empty until `receive_body`, so the
+    /// request can be validated before the client is asked to send it.
+    pub fn new(parts: &Parts, conn: &ConnectionInfo, trust_proxy: bool) -> Self {
+        let method = parts.method.as_str().to_string();
+        let uri = &parts.uri;
+        let url = uri.path_and_query().map_or_else(|| uri.path().to_string(), |pq| pq.to_string());
         let pathname = uri.path().to_string();
         let search = uri.query().map(|q| format!("?{q}"));
         let original_url = url.clone();
 
-        let mut headers_raw = HashMap::with_capacity(req.headers().len());
-        for (name, value) in req.headers() {
+        let mut headers_raw = HashMap::with_capacity(parts.headers.len());
+        for (name, value) in &parts.headers {
             if let Ok(v) = value.to_str() {
                 headers_raw.insert(name.as_str().to_string(), v.to_string());
             }
@@ -60,21 +85,27 @@ impl RequestCore {
             }
         }
 
-        let scheme = req.uri().scheme_str().unwrap_or("http").to_string();
+        // HTTP/2 carries the host in the `:authority` pseudo-header instead of `Host`.
+        let authority = uri.authority().map(|a| a.to_string());
 
-        let body = req.into_body().collect().await?.to_bytes();
+        let scheme = if conn.secure {
+            "https".to_string()
+        } else {
+            uri.scheme_str().unwrap_or("http").to_string()
+        };
 
         let protocol = if trust_proxy {
-            headers_raw
-                .get("x-forwarded-proto")
-                .map(|s| s.to_string())
-                .unwrap_or_else(|| "http".to_string())
+            headers_raw.get("x-forwarded-proto").map(|s| s.to_string()).unwrap_or(scheme)
         } else {
             scheme
         };
 
         let secure = protocol.eq_ignore_ascii_case("https");
-        let hostname = headers_raw.get("host").cloned().unwrap_or_else(|| "localhost".to_string());
+        let hostname = headers_raw
+            .get("host")
+            .cloned()
+            .or(authority)
+            .unwrap_or_else(|| "localhost".to_string());
 
         let cookies_raw = headers_raw.get("cookie").map_or(HashMap::new(), |cookie_str| {
             cookie_str
@@ -102,13 +133,13 @@ impl RequestCore {
         let ip = ips
             .first()
             .cloned()
-            .or_else(|| remote_addr.map(|a| a.ip().to_string()))
+            .or_else(|| conn.remote_addr.map(|a| a.ip().to_string()))
             .unwrap_or_default();
 
         let xhr =
             headers_raw.get("x-requested-with").map(|v| v == "XMLHttpRequest").unwrap_or(false);
 
-        Ok(Self {
+        Self {
             method,
             url,
             pathname,
@@ -120,12 +151,52 @@ impl RequestCore {
             xhr,
             ip,
             ips,
-            body,
+            peer_credentials: conn.peer_credentials,
+            body: Bytes::new(),
             headers_raw,
             params: HashMap::new(),
+            numeric_params: HashMap::new(),
             query_raw,
             cookies_raw,
-        })
+        }
+    }
+
+    /// Read the whole request body, enforcing the size limit and read timeout.
+    pub async fn receive_body<B>(
+        &mut self,
+        body: B,
+        limit: Option<usize>,
+        timeout: Option<Duration>,
+    ) -> Result<(), BodyError>
+    where
+        B: Body<Data = Bytes>,
+        B::Error: Into<Box<dyn Error + Send + Sync>>,
+    {
+        self.body = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, read_body(body, limit))
+                .await
+                .map_err(|_| BodyError::Timeout)??,
+            None => read_body(body, limit).await?,
+        };
+
+        Ok(())
+    }
+}
+
+async fn read_body<B>(body: B, limit: Option<usize>) -> Result<Bytes, BodyError>
+where
+    B: Body<Data = Bytes>,
+    B::Error: Into<Box<dyn Error + Send + Sync>>,
+{
+    match limit {
+        Some(limit) => Ok(Limited::new(body, limit)
+            .collect()
+            .await
+            .map_err(|e| {
+                if e.is::<LengthLimitError>() { BodyError::TooLarge } else { BodyError::Read(e) }
+            })?
+            .to_bytes()),
+        None => Ok(body.collect().await.map_err(|e| BodyError::Read(e.into()))?.to_bytes()),
     }
 }
 
@@ -164,6 +235,11 @@ pub fn get_all_params(core: &External<Arc<RequestCore>>) -> HashMap<String, Stri
     core.params.clone()
 }
 
+#[napi]
+pub fn get_numeric_params(core: &External<Arc<RequestCore>>) -> HashMap<String, f64> {
+    core.numeric_params.clone()
+}
+
 #[napi]
 pub fn get_cookie(core: &External<Arc<RequestCore>>, name: String) -> Option<String> {
     core.cookies_raw.get(&name).cloned()
@@ -214,6 +290,11 @@ pub fn get_ips(core: &External<Arc<RequestCore>>) -> Vec<String> {
     core.ips.clone()
 }
 
+#[napi]
+pub fn get_peer_credentials(core: &External<Arc<RequestCore>>) -> Option<PeerCredentialsCore> {
+    core.peer_credentials
+}
+
 #[napi]
 pub fn get_secure(core: &External<Arc<RequestCore>>) -> bool {
     core.secure
@@ -1,9 +1,30 @@
+pub mod connection;
+pub mod connection_tests;
 pub mod context;
 pub mod core;
+pub mod core_tests;
+pub mod cors;
+pub mod cors_tests;
 pub mod handler;
+pub mod handler_tests;
+pub mod host;
+pub mod host_tests;
+pub mod lifecycle;
+pub mod lifecycle_tests;
+pub mod listener;
+pub mod listener_tests;
+pub mod params;
+pub mod params_tests;
+pub mod path;
+pub mod path_tests;
+pub mod proxy_protocol;
+pub mod proxy_protocol_tests;
 pub mod router;
+pub mod router_tests;
 pub mod routes;
+pub mod tls;
+pub mod tls_tests;
 
 pub use core::ServerCore;
 pub use router::{GlobalRouter, HttpRouter};
-pub use routes::{CompiledRoute, ROUTER, Route};
+pub use routes::{CompiledRoute, Route};
@@ -0,0 +1,479 @@
+use hyper::{
+    rt::{Read, Write},
+    service::service_fn,
+};
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo, TokioTimer},
+    server::conn::auto,
+};
+use tokio_rustls::{TlsAcceptor, server::TlsStream};
+
+use dashmap::DashMap;
+
+use std::{
+    future::pending,
+    net::{IpAddr, SocketAddr},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::{Notify, OwnedSemaphorePermit, Semaphore, watch},
+};
+
+use crate::server::{
+    core::ServerOptionsCore,
+    cors::CorsPolicy,
+    handler::{handle_request, service_unavailable},
+    lifecycle::{ConnectionLifecycle, Tracked},
+    proxy_protocol::read_proxy_header,
+    router::GlobalRouter,
+};
+
+pub type ConnectionBuilder = auto::Builder<TokioExecutor>;
+
+/// hyper's own default for `header_read_timeout`, reused for the PROXY protocol header and
+/// the TLS handshake.
+const DEFAULT_HEADER_READ_TIMEOUT_MS: u32 = 30_000;
+
+/// How long a connection turned away by a limit may stay open to receive its 503. These
+/// connections no longer count against the limits, so they get a short, fixed lifetime.
+const REJECTED_CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Smallest read buffer hyper accepts; it panics below this.
+const MIN_HEADER_BUF_SIZE: usize = 8192;
+
+/// Transport-level facts about an accepted connection that requests need to know.
+#[derive(Clone, Copy, Default)]
+pub struct ConnectionInfo {
+    pub remote_addr: Option<SocketAddr>,
+    pub secure: bool,
+    /// Credentials of the process on the other end of a Unix socket.
+    pub peer_credentials: Option<PeerCredentialsCore>,
+}
+
+/// Identity of the peer process of a Unix socket connection (`SO_PEERCRED` and equivalents).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[napi(object)]
+pub struct PeerCredentialsCore {
+    pub uid: u32,
+    pub gid: u32,
+    /// Not reported on every platform.
+    pub pid: Option<i32>,
+}
+
+/// HTTP/1 connection tuning. Unset fields keep hyper's defaults.
+#[derive(Clone, Default)]
+#[napi(object)]
+pub struct Http1OptionsCore {
+    /// Reuse connections for several requests. Default: true.
+    pub keep_alive: Option<bool>,
+    /// Keep the connection writable after the client shuts down its write half. Default: false.
+    pub half_close: Option<bool>,
+    /// Flush pipelined responses together instead of one at a time. Default: false.
+    pub pipeline_flush: Option<bool>,
+    /// Maximum number of request headers (431 when exceeded). Default: 100.
+    pub max_headers: Option<u32>,
+    /// Maximum size in bytes of the request head (431 when exceeded). Minimum 8192,
+    /// default ~400KB.
+    pub max_header_size: Option<u32>,
+}
+
+/// HTTP/2 tuning. HTTP/2 is negotiated through ALPN on TLS listeners and
+/// detected from the prior-knowledge preface (h2c) on plaintext ones.
+#[derive(Clone, Default)]
+#[napi(object)]
+pub struct Http2OptionsCore {
+    pub enabled: Option<bool>,
+    pub max_concurrent_streams: Option<u32>,
+    pub initial_stream_window_size: Option<u32>,
+    pub initial_connection_window_size: Option<u32>,
+    /// Interval in milliseconds between keep-alive pings. Pings are disabled when unset.
+    pub keep_alive_interval: Option<u32>,
+    /// Time in milliseconds to wait for a ping acknowledgement before closing the connection.
+    pub keep_alive_timeout: Option<u32>,
+}
+
+/// Lifecycle state broadcast from `ServerCore` to its listeners and connections.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Shutdown {
+    Running,
+    /// Stop accepting and let open connections finish their in-flight requests.
+    Graceful,
+    /// Stop accepting and drop open connections right away.
+    Immediate,
+}
+
+/// Connection counters reported to JavaScript.
+#[napi(object)]
+pub struct ConnectionStatsCore {
+    /// Open connections admitted by the connection limits.
+    pub active: u32,
+    /// Connections turned away by `max_connections` or `max_connections_per_ip`.
+    pub rejected: i64,
+    /// Distinct client IPs with at least one open connection.
+    pub clients: u32,
+}
+
+/// Counts live connections, globally and per client IP, so the server can enforce
+/// connection limits and a graceful shutdown can wait for them to drain.
+#[derive(Default)]
+pub struct ConnectionTracker {
+    active: AtomicUsize,
+    rejected: AtomicU64,
+    per_ip: DashMap<IpAddr, usize>,
+    idle: Notify,
+}
+
+impl ConnectionTracker {
+    pub fn track(self: &Arc<Self>) -> ConnectionGuard {
+        self.track_in(None)
+    }
+
+    /// Like `track`, holding `slot` (see `accept_slots`) for as long as the connection is open.
+    pub fn track_in(self: &Arc<Self>, slot: Option<OwnedSemaphorePermit>) -> ConnectionGuard {
+        self.active.fetch_add(1, Ordering::AcqRel);
+        ConnectionGuard { tracker: self.clone(), ip: None, _slot: slot }
+    }
+
+    pub fn active(&self) -> usize {
+        self.active.load(Ordering::Acquire)
+    }
+
+    pub fn active_from(&self, ip: IpAddr) -> usize {
+        self.per_ip.get(&ip).map_or(0, |count| *count)
+    }
+
+    pub fn stats(&self) -> ConnectionStatsCore {
+        ConnectionStatsCore {
+            active: self.active() as u32,
+            rejected: self.rejected.load(Ordering::Relaxed) as i64,
+            clients: self.per_ip.len() as u32,
+        }
+    }
+
+    /// Resolves once fewer than `limit` connections are open.
+    pub async fn wait_idle(&self) {
+        loop {
+            let notified = self.idle.notified();
+
+            if self.active() == 0 {
+                return;
+            }
+
+            notified.await;
+        }
+    }
+}
+
+pub struct ConnectionGuard {
+    tracker: Arc<ConnectionTracker>,
+    ip: Option<IpAddr>,
+    _slot: Option<OwnedSemaphorePermit>,
+}
+
+impl ConnectionGuard {
+    /// Count this connection against `ip`, unless `limit` connections from it are already open.
+    pub fn track_ip(&mut self, ip: IpAddr, limit: Option<usize>) -> bool {
+        let mut count = self.tracker.per_ip.entry(ip).or_insert(0);
+
+        if limit.is_some_and(|limit| *count >= limit) {
+            drop(count);
+            self.tracker.per_ip.remove_if(&ip, |_, count| *count == 0);
+            return false;
+        }
+
+        *count += 1;
+        self.ip = Some(ip);
+        true
+    }
+
+    /// Count the connection as rejected and stop counting it as active.
+    pub fn reject(self) {
+        self.tracker.rejected.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        if let Some(ip) = self.ip {
+            if let Some(mut count) = self.tracker.per_ip.get_mut(&ip) {
+                *count -= 1;
+            }
+            self.tracker.per_ip.remove_if(&ip, |_, count| *count == 0);
+        }
+
+        if self.tracker.active.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.tracker.idle.notify_waiters();
+        }
+    }
+}
+
+/// State shared by every connection accepted on a listener.
+#[derive(Clone)]
+pub struct ServeContext {
+    pub builder: Arc<ConnectionBuilder>,
+    pub router: Arc<GlobalRouter>,
+    /// Options without the TLS material, which only the acceptors need.
+    pub config: Arc<ServerOptionsCore>,
+    pub cors: Option<Arc<CorsPolicy>>,
+    pub shutdown: watch::Receiver<Shutdown>,
+}
+
+pub fn http2_enabled(config: &ServerOptionsCore) -> bool {
+    config.http2.as_ref().and_then(|h2| h2.enabled).unwrap_or(true)
+}
+
+/// One slot per connection allowed by `max_connections` when it pauses accepting. Every
+/// listener takes a slot before it accepts, so together they cannot go over the limit.
+pub fn accept_slots(config: &ServerOptionsCore) -> Option<Arc<Semaphore>> {
+    match config.connection_limit_action.as_deref() {
+        Some("reject") => None,
+        _ => config.max_connections.map(|max| Arc::new(Semaphore::new(max as usize))),
+    }
+}
+
+/// Whether a connection fits under `max_connections` (in `reject` mode) and
+/// `max_connections_per_ip`. Connections that don't lose their guard, and are answered
+/// with 503 and closed.
+fn admit(
+    config: &ServerOptionsCore,
+    mut guard: ConnectionGuard,
+    conn: &ConnectionInfo,
+) -> Option<ConnectionGuard> {
+    let over_capacity = config.connection_limit_action.as_deref() == Some("reject")
+        && config.max_connections.is_some_and(|max| guard.tracker.active() > max as usize);
+
+    let admitted = !over_capacity
+        && conn.remote_addr.is_none_or(|addr| {
+            guard.track_ip(addr.ip(), config.max_connections_per_ip.map(|max| max as usize))
+        });
+
+    if !admitted {
+        guard.reject();
+        return None;
+    }
+
+    Some(guard)
+}
+
+pub fn build_connection_builder(config: &ServerOptionsCore) -> ConnectionBuilder {
+    let mut builder = auto::Builder::new(TokioExecutor::new());
+
+    let mut h1 = builder.http1();
+    h1.timer(TokioTimer::new());
+
+    if let Some(timeout) = config.header_read_timeout {
+        h1.header_read_timeout(Duration::from_millis(timeout as u64));
+    }
+
+    let h1_options = config.http1.clone().unwrap_or_default();
+    h1.keep_alive(h1_options.keep_alive.unwrap_or(true))
+        .half_close(h1_options.half_close.unwrap_or(false))
+        .pipeline_flush(h1_options.pipeline_flush.unwrap_or(false));
+
+    if let Some(max) = h1_options.max_headers {
+        h1.max_headers(max as usize);
+    }
+
+    if let Some(size) = h1_options.max_header_size {
+        h1.max_buf_size((size as usize).max(MIN_HEADER_BUF_SIZE));
+    }
+
+    if !http2_enabled(config) {
+        return builder.http1_only();
+    }
+
+    let h2_options = config.http2.clone().unwrap_or_default();
+    let mut h2 = builder.http2();
+    h2.timer(TokioTimer::new())
+        .max_concurrent_streams(h2_options.max_concurrent_streams)
+        .initial_stream_window_size(h2_options.initial_stream_window_size)
+        .initial_connection_window_size(h2_options.initial_connection_window_size)
+        .keep_alive_interval(
+            h2_options.keep_alive_interval.map(|ms| Duration::from_millis(ms as u64)),
+        );
+
+    if let Some(timeout) = h2_options.keep_alive_timeout {
+        h2.keep_alive_timeout(Duration::from_millis(timeout as u64));
+    }
+
+    builder
+}
+
+/// Serve an accepted stream, reading its PROXY protocol header and terminating TLS first
+/// when the listener asks for them.
+pub async fn serve_stream<S>(
+    mut stream: S,
+    tls: Option<TlsAcceptor>,
+    proxy_protocol: bool,
+    ctx: ServeContext,
+    mut conn: ConnectionInfo,
+    guard: ConnectionGuard,
+) where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    // The PROXY header and the TLS handshake come before the request head, so they share
+    // its deadline.
+    let head_timeout = Duration::from_millis(
+        ctx.config.header_read_timeout.unwrap_or(DEFAULT_HEADER_READ_TIMEOUT_MS) as u64,
+    );
+
+    if proxy_protocol {
+        let header = tokio::time::timeout(head_timeout, read_proxy_header(&mut stream));
+        let stopping = wait_for_shutdown(ctx.shutdown.clone(), |s| s != Shutdown::Running);
+
+        let header = tokio::select! {
+            res = header => res,
+            _ = stopping => return,
+        };
+
+        match header {
+            Ok(Ok(addr)) => conn.remote_addr = addr.or(conn.remote_addr),
+            Ok(Err(err)) => {
+                eprintln!("Invalid PROXY protocol header: {err}");
+                return;
+            }
+            Err(_) => return,
+        }
+    }
+
+    // A rejected connection stops counting against the limits before it is answered.
+    let guard = admit(&ctx.config, guard, &conn);
+    let rejected = guard.is_none();
+    let serving = serve_secured(stream, tls, ctx, conn, guard, head_timeout);
+
+    if rejected {
+        let _ = tokio::time::timeout(REJECTED_CONNECTION_TIMEOUT, serving).await;
+    } else {
+        serving.await;
+    }
+}
+
+/// Terminate TLS if the listener has an acceptor, then serve HTTP.
+async fn serve_secured<S>(
+    stream: S,
+    tls: Option<TlsAcceptor>,
+    ctx: ServeContext,
+    conn: ConnectionInfo,
+    guard: Option<ConnectionGuard>,
+    handshake_timeout: Duration,
+) where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let Some(acceptor) = tls else {
+        return serve_connection(TokioIo::new(stream), ctx, conn, guard).await;
+    };
+
+    let stopping = wait_for_shutdown(ctx.shutdown.clone(), |s| s != Shutdown::Running);
+
+    let handshake = tokio::select! {
+        res = accept_tls(&acceptor, stream, conn, handshake_timeout) => res,
+        _ = stopping => return,
+    };
+
+    if let Some((stream, conn)) = handshake {
+        serve_connection(TokioIo::new(stream), ctx, conn, guard).await
+    }
+}
+
+/// Complete the TLS handshake on `stream` within `timeout`, marking `conn` as secure.
+pub async fn accept_tls<S>(
+    acceptor: &TlsAcceptor,
+    stream: S,
+    conn: ConnectionInfo,
+    timeout: Duration,
+) -> Option<(TlsStream<S>, ConnectionInfo)>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    match tokio::time::timeout(timeout, acceptor.accept(stream)).await {
+        Ok(Ok(stream)) => Some((stream, ConnectionInfo { secure: true, ..conn })),
+        Ok(Err(err)) => {
+            eprintln!("TLS handshake failed: {err}");
+            None
+        }
+        Err(_) => None,
+    }
+}
+
+/// Serve HTTP on `io`. Connections without a guard were turned away by a connection
+/// limit: their first request gets 503 and the connection is closed after it (with
+/// `Connection: close` on HTTP/1, GOAWAY on HTTP/2).
+///
+/// The connection is wound down gracefully on server shutdown, after
+/// `max_requests_per_connection` requests, or after `keep_alive_timeout` without activity.
+pub async fn serve_connection<I>(
+    io: I,
+    ctx: ServeContext,
+    conn: ConnectionInfo,
+    guard: Option<ConnectionGuard>,
+) where
+    I: Read + Write + Unpin + Send + 'static,
+{
+    let ServeContext { builder, router, config, cors, shutdown } = ctx;
+    let admitted = guard.is_some();
+
+    let lifecycle = if admitted {
+        ConnectionLifecycle::from_config(&config)
+    } else {
+        let idle_timeout = config.keep_alive_timeout.map(|ms| Duration::from_millis(ms as u64));
+        ConnectionLifecycle::new(Some(1), idle_timeout)
+    };
+    let requests = lifecycle.clone();
+
+    let connection = builder.serve_connection(
+        Tracked::new(io, lifecycle.clone()),
+        service_fn(move |req| {
+            let (router, config, cors) = (router.clone(), config.clone(), cors.clone());
+            let request = requests.begin_request();
+
+            async move {
+                let _request = request;
+
+                if !admitted {
+                    return Ok(service_unavailable(req.version()));
+                }
+
+                handle_request(req, router, config, conn, cors).await
+            }
+        }),
+    );
+    tokio::pin!(connection);
+
+    let stopping = async {
+        tokio::select! {
+            _ = wait_for_shutdown(shutdown.clone(), |s| s != Shutdown::Running) => {}
+            _ = lifecycle.retired() => {}
+        }
+    };
+
+    let result = tokio::select! {
+        res = connection.as_mut() => res,
+        _ = stopping => {
+            connection.as_mut().graceful_shutdown();
+
+            tokio::select! {
+                res = connection.as_mut() => res,
+                _ = wait_for_shutdown(shutdown, |s| s == Shutdown::Immediate) => Ok(()),
+            }
+        }
+    };
+
+    if let Err(err) = result {
+        eprintln!("Error serving connection: {err:?}");
+    }
+}
+
+/// Resolves once the shutdown state satisfies `predicate`; never resolves if the server is gone.
+pub async fn wait_for_shutdown(
+    mut rx: watch::Receiver<Shutdown>,
+    predicate: impl Fn(Shutdown) -> bool,
+) {
+    if rx.wait_for(|s| predicate(*s)).await.is_err() {
+        pending::<()>().await;
+    }
+}
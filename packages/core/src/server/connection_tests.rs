@@ -0,0 +1,290 @@
+#[cfg(test)]
+mod tests {
+    use super::super::connection::*;
+    use super::super::core::ServerOptionsCore;
+    use super::super::params::RoutePattern;
+    use super::super::router::GlobalRouter;
+    use super::super::routes::{CompiledRoute, ResponseStrategy};
+    use http_body_util::{BodyExt, Empty, Full};
+    use hyper::{Request, Response, body::Bytes, client::conn};
+    use hyper_util::rt::{TokioExecutor, TokioIo};
+    use std::{net::IpAddr, sync::Arc, time::Duration};
+    use tokio::{io::DuplexStream, sync::watch, task::JoinHandle};
+
+    #[tokio::test]
+    async fn test_tracker_counts_guards() {
+        let tracker = Arc::new(ConnectionTracker::default());
+
+        let first = tracker.track();
+        let second = tracker.track();
+        assert_eq!(tracker.active(), 2);
+
+        drop(first);
+        assert_eq!(tracker.active(), 1);
+
+        drop(second);
+        assert_eq!(tracker.active(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_wait_idle_resolves_when_last_guard_drops() {
+        let tracker = Arc::new(ConnectionTracker::default());
+        let guard = tracker.track();
+
+        let waiter = tokio::spawn({
+            let tracker = tracker.clone();
+            async move { tracker.wait_idle().await }
+        });
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!waiter.is_finished());
+
+        drop(guard);
+        tokio::time::timeout(Duration::from_secs(1), waiter).await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_wait_idle_without_connections() {
+        let tracker = ConnectionTracker::default();
+        tokio::time::timeout(Duration::from_millis(50), tracker.wait_idle()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_shutdown_matches_state() {
+        let (tx, rx) = watch::channel(Shutdown::Running);
+
+        let immediate = tokio::spawn(wait_for_shutdown(rx.clone(), |s| s == Shutdown::Immediate));
+
+        tx.send(Shutdown::Graceful).unwrap();
+        tokio::time::timeout(
+            Duration::from_millis(50),
+            wait_for_shutdown(rx, |s| s != Shutdown::Running),
+        )
+        .await
+        .unwrap();
+        assert!(!immediate.is_finished());
+
+        tx.send(Shutdown::Immediate).unwrap();
+        tokio::time::timeout(Duration::from_secs(1), immediate).await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_track_ip_enforces_limit_and_releases() {
+        let tracker = Arc::new(ConnectionTracker::default());
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        let other: IpAddr = "198.51.100.4".parse().unwrap();
+
+        let mut first = tracker.track();
+        assert!(first.track_ip(ip, Some(1)));
+
+        let mut second = tracker.track();
+        assert!(!second.track_ip(ip, Some(1)));
+        assert!(second.track_ip(other, Some(1)));
+        assert_eq!(tracker.active_from(ip), 1);
+        assert_eq!(tracker.stats().clients, 2);
+
+        drop(first);
+        assert_eq!(tracker.active_from(ip), 0);
+        assert_eq!(tracker.stats().clients, 1);
+
+        let mut third = tracker.track();
+        assert!(third.track_ip(ip, Some(1)));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_connections_are_counted() {
+        let tracker = Arc::new(ConnectionTracker::default());
+        let ip: IpAddr = "::1".parse().unwrap();
+
+        let mut guard = tracker.track();
+        assert!(!guard.track_ip(ip, Some(0)));
+        guard.reject();
+
+        let stats = tracker.stats();
+        assert_eq!((stats.active, stats.rejected, stats.clients), (0, 1, 0));
+    }
+
+    #[tokio::test]
+    async fn test_accept_slots_are_shared_until_a_connection_closes() {
+        let config = ServerOptionsCore { max_connections: Some(2), ..Default::default() };
+        let slots = accept_slots(&config).unwrap();
+        let tracker = Arc::new(ConnectionTracker::default());
+
+        // Two listeners take the two slots; a third has to wait for a connection to close.
+        let first = tracker.track_in(slots.clone().acquire_owned().await.ok());
+        let _second = tracker.track_in(slots.clone().acquire_owned().await.ok());
+
+        let waiter = tokio::spawn(slots.clone().acquire_owned());
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!waiter.is_finished());
+
+        drop(first);
+        let slot = tokio::time::timeout(Duration::from_secs(1), waiter).await.unwrap().unwrap();
+        let _third = tracker.track_in(slot.ok());
+        assert_eq!(tracker.active(), 2);
+        assert_eq!(slots.available_permits(), 0);
+
+        let reject =
+            ServerOptionsCore { connection_limit_action: Some("reject".to_string()), ..config };
+        assert!(accept_slots(&reject).is_none());
+    }
+
+    fn static_route(method: &str, path: &str, body: &'static str) -> CompiledRoute {
+        let pattern = RoutePattern::parse(path).unwrap();
+        CompiledRoute {
+            method: method.into(),
+            original_path: path.into(),
+            path: pattern.path.into(),
+            shape: pattern.shape.into(),
+            params: pattern.params.into(),
+            segments: Box::new([]),
+            strategy: ResponseStrategy::FullStatic(Response::new(Full::new(Bytes::from(body)))),
+            schema: None,
+            max_request_size: None,
+            timeout: None,
+            case_insensitive: false,
+            host: None,
+        }
+    }
+
+    /// Serve one end of an in-memory stream as a connection from `203.0.113.7`. Returns
+    /// the client end and the serving task.
+    fn serve(
+        router: GlobalRouter,
+        config: ServerOptionsCore,
+        tracker: &Arc<ConnectionTracker>,
+        shutdown: watch::Receiver<Shutdown>,
+    ) -> (DuplexStream, JoinHandle<()>) {
+        let ctx = ServeContext {
+            builder: Arc::new(build_connection_builder(&config)),
+            router: Arc::new(router),
+            config: Arc::new(config),
+            cors: None,
+            shutdown,
+        };
+        let conn = ConnectionInfo {
+            remote_addr: Some("203.0.113.7:4000".parse().unwrap()),
+            ..Default::default()
+        };
+
+        let (client, server) = tokio::io::duplex(16 * 1024);
+        let serving = tokio::spawn(serve_stream(server, None, false, ctx, conn, tracker.track()));
+        (client, serving)
+    }
+
+    /// Serve a connection from an IP that is already at its `max_connections_per_ip` limit.
+    fn serve_rejected(
+        tracker: &Arc<ConnectionTracker>,
+        shutdown: watch::Receiver<Shutdown>,
+    ) -> (DuplexStream, JoinHandle<()>) {
+        let config = ServerOptionsCore { max_connections_per_ip: Some(0), ..Default::default() };
+        serve(GlobalRouter::new(), config, tracker, shutdown)
+    }
+
+    fn request() -> Request<Empty<Bytes>> {
+        Request::get("http://localhost/").body(Empty::new()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rejected_http1_connection_is_answered_once_and_closed() {
+        let tracker = Arc::new(ConnectionTracker::default());
+        let (_shutdown_tx, shutdown_rx) = watch::channel(Shutdown::Running);
+        let (client, serving) = serve_rejected(&tracker, shutdown_rx);
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let stats = tracker.stats();
+        assert_eq!((stats.active, stats.rejected), (0, 1));
+
+        let (mut sender, connection) = conn::http1::handshake(TokioIo::new(client)).await.unwrap();
+        tokio::spawn(connection);
+
+        let response = sender.send_request(request()).await.unwrap();
+        assert_eq!(response.status(), 503);
+        assert_eq!(response.headers()["connection"], "close");
+
+        tokio::time::timeout(Duration::from_secs(1), serving).await.unwrap().unwrap();
+        assert!(sender.send_request(request()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rejected_http2_connection_is_answered_once_and_closed() {
+        let tracker = Arc::new(ConnectionTracker::default());
+        let (_shutdown_tx, shutdown_rx) = watch::channel(Shutdown::Running);
+        let (client, serving) = serve_rejected(&tracker, shutdown_rx);
+
+        let (mut sender, connection) =
+            conn::http2::handshake(TokioExecutor::new(), TokioIo::new(client)).await.unwrap();
+        tokio::spawn(connection);
+
+        let response = sender.send_request(request()).await.unwrap();
+        assert_eq!(response.status(), 503);
+
+        tokio::time::timeout(Duration::from_secs(1), serving).await.unwrap().unwrap();
+        assert!(sender.send_request(request()).await.is_err());
+        assert_eq!(tracker.active(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_head_falls_back_to_get_without_a_body() {
+        let router = GlobalRouter::new();
+        router.insert("GET", static_route("GET", "/greeting", "hello world")).unwrap();
+
+        let tracker = Arc::new(ConnectionTracker::default());
+        let (_shutdown_tx, shutdown_rx) = watch::channel(Shutdown::Running);
+        let (client, _serving) = serve(router, ServerOptionsCore::default(), &tracker, shutdown_rx);
+
+        let (mut sender, connection) = conn::http1::handshake(TokioIo::new(client)).await.unwrap();
+        tokio::spawn(connection);
+
+        let head = Request::head("http://localhost/greeting").body(Empty::<Bytes>::new()).unwrap();
+        let response = sender.send_request(head).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()["content-length"], "11");
+        assert!(response.into_body().collect().await.unwrap().to_bytes().is_empty());
+
+        // The connection is still usable: the HEAD response left nothing unread.
+        let get = Request::get("http://localhost/greeting").body(Empty::new()).unwrap();
+        let response = sender.send_request(get).await.unwrap();
+        assert_eq!(response.headers()["content-length"], "11");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"hello world");
+    }
+
+    #[tokio::test]
+    async fn test_cleartext_http2_with_prior_knowledge() {
+        let router = GlobalRouter::new();
+        router.insert("GET", static_route("GET", "/greeting", "hello world")).unwrap();
+
+        let tracker = Arc::new(ConnectionTracker::default());
+        let (_shutdown_tx, shutdown_rx) = watch::channel(Shutdown::Running);
+        let (client, _serving) = serve(router, ServerOptionsCore::default(), &tracker, shutdown_rx);
+
+        let (mut sender, connection) =
+            conn::http2::handshake(TokioExecutor::new(), TokioIo::new(client)).await.unwrap();
+        tokio::spawn(connection);
+
+        for _ in 0..2 {
+            let get =
+                Request::get("http://localhost/greeting").body(Empty::<Bytes>::new()).unwrap();
+            let response = sender.send_request(get).await.unwrap();
+            assert_eq!(response.version(), hyper::Version::HTTP_2);
+            assert_eq!(response.status(), 200);
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            assert_eq!(&body[..], b"hello world");
+        }
+        assert_eq!(tracker.active(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_silent_rejected_connection_is_closed() {
+        let tracker = Arc::new(ConnectionTracker::default());
+        let (_shutdown_tx, shutdown_rx) = watch::channel(Shutdown::Running);
+
+        // The client never sends a request, and no keep-alive timeout is configured.
+        let (_client, serving) = serve_rejected(&tracker, shutdown_rx);
+
+        tokio::time::timeout(Duration::from_secs(60), serving).await.unwrap().unwrap();
+        assert_eq!(tracker.stats().rejected, 1);
+    }
+}
@@ -1,46 +1,115 @@
-use hyper::server::conn::http1;
-use hyper_util::rt::TokioIo;
+use futures_util::future::join_all;
 
 use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
 use napi_derive::napi;
 
-use std::net::SocketAddr;
-use tokio::{net::TcpListener, sync::watch};
-
-#[cfg(unix)]
-use std::fs;
-#[cfg(unix)]
-use std::path::Path;
-#[cfg(unix)]
-use tokio::net::UnixListener;
-
-use crate::server::{handler::handle_request, routes::insert_route};
+use parking_lot::Mutex;
+
+use std::{net::IpAddr, sync::Arc, time::Duration};
+use tokio::sync::{Semaphore, watch};
+
+use crate::server::{
+    connection::{
+        ConnectionStatsCore, ConnectionTracker, Http1OptionsCore, Http2OptionsCore, ServeContext,
+        Shutdown, accept_slots, build_connection_builder, serve_stream,
+    },
+    cors::{CorsOptionsCore, CorsPolicy},
+    listener::{
+        BoundListener, ListenerOptionsCore, ServerAddressCore, SocketOptionsCore, listener_specs,
+        release_listeners,
+    },
+    path::RoutingOptionsCore,
+    router::{GlobalRouter, HttpRouter},
+    routes::{
+        RouteCountCore, RouteInfoCore, insert_route, remove_route, replace_route, swap_routes,
+    },
+    tls::TlsOptionsCore,
+};
 
 use super::routes::Route;
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 #[napi(object)]
 pub struct ServerOptionsCore {
     pub port: Option<u16>,
     pub host: Option<String>,
+    /// Path of the Unix socket, or `@name` for a Linux abstract-namespace socket.
     pub unix_socket: Option<String>,
+    /// Permission bits for Unix socket files (e.g. `0o660`).
+    pub unix_socket_mode: Option<u32>,
+    /// Group name or numeric gid that should own Unix socket files.
+    pub unix_socket_group: Option<String>,
+    /// Listen on several addresses at once. Replaces `port`/`host`/`unix_socket` when non-empty.
+    pub listeners: Option<Vec<ListenerOptionsCore>>,
+    /// Also listen on sockets passed by systemd (`LISTEN_FDS` / `LISTEN_PID`).
+    pub socket_activation: Option<bool>,
     pub trust_proxy: Option<bool>,
+    /// Answer 405 with an `Allow` header when the path is routed for other methods only,
+    /// instead of 404. Default: true.
+    pub method_not_allowed: Option<bool>,
+    /// Trailing-slash policy and path normalization applied before routing.
+    pub routing: Option<RoutingOptionsCore>,
+    /// Answer CORS preflights natively and add CORS headers to every response.
+    pub cors: Option<CorsOptionsCore>,
+    /// Expect a PROXY protocol (v1 or v2) header on every connection and take the client
+    /// address from it. Only enable behind a balancer that always sends one.
+    pub proxy_protocol: Option<bool>,
     pub max_request_size: Option<u32>,
+    /// Time in milliseconds a handler has to start its response before the client gets 504.
     pub timeout: Option<u32>,
+    /// Time in milliseconds a client has to send the request head (HTTP/1), and also the
+    /// PROXY protocol header and the TLS handshake. Default: 30s.
+    pub header_read_timeout: Option<u32>,
+    /// Time in milliseconds a client has to send the whole request body before getting 408.
+    pub body_read_timeout: Option<u32>,
     pub reuse_port: Option<bool>,
+    pub socket: Option<SocketOptionsCore>,
+    pub tls: Option<TlsOptionsCore>,
+    pub http1: Option<Http1OptionsCore>,
+    pub http2: Option<Http2OptionsCore>,
+    /// Close connections that have had no traffic and no request in progress for this many
+    /// milliseconds (HTTP/1 keep-alive and HTTP/2 alike).
+    pub keep_alive_timeout: Option<u32>,
+    /// Close connections gracefully after they have served this many requests.
+    pub max_requests_per_connection: Option<u32>,
+    /// Maximum time in milliseconds `shutdown` waits for open connections to drain.
+    pub shutdown_timeout: Option<u32>,
+    /// Maximum number of open connections across all listeners.
+    pub max_connections: Option<u32>,
+    /// Maximum number of open connections from a single client IP. Extra ones get 503.
+    pub max_connections_per_ip: Option<u32>,
+    /// What to do at `max_connections`: stop accepting until a connection closes (`pause`,
+    /// the default) or accept and answer 503 (`reject`).
+    #[napi(ts_type = "'pause' | 'reject'")]
+    pub connection_limit_action: Option<String>,
 }
 
+const DEFAULT_SHUTDOWN_TIMEOUT_MS: u32 = 10_000;
+
+pub type ReadyCallback =
+    ThreadsafeFunction<Vec<ServerAddressCore>, (), Vec<ServerAddressCore>, napi::Status, false>;
+
 #[napi]
 pub struct ServerCore {
     config: ServerOptionsCore,
-    shutdown_tx: Option<watch::Sender<()>>,
+    shutdown_tx: Option<watch::Sender<Shutdown>>,
+    connections: Arc<ConnectionTracker>,
+    addresses: Mutex<Vec<ServerAddressCore>>,
+    /// Routes of this server only; every instance starts with an empty table.
+    pub(crate) router: Arc<GlobalRouter>,
 }
 
 #[napi]
 impl ServerCore {
     #[napi(constructor)]
     pub fn new(config: ServerOptionsCore) -> Self {
-        ServerCore { config, shutdown_tx: None }
+        ServerCore {
+            config,
+            shutdown_tx: None,
+            connections: Arc::new(ConnectionTracker::default()),
+            addresses: Mutex::new(Vec::new()),
+            router: Arc::new(GlobalRouter::new()),
+        }
     }
 
     #[napi]
@@ -55,10 +124,60 @@ impl ServerCore {
 
     #[napi]
     pub fn add_route(&mut self, route: Route) -> napi::Result<()> {
-        insert_route(route)
+        insert_route(&self.router, self.with_defaults(route))
+    }
+
+    /// Remove the route registered for `method`, `path` and `host`; `false` if there was
+    /// none. Requests already being handled by it are not affected.
+    #[napi]
+    pub fn remove_route(&self, method: String, path: String, host: Option<String>) -> bool {
+        remove_route(&self.router, &method, &path, host.as_deref())
+    }
+
+    /// Put `route` in place of the route registered for the same method, path and host,
+    /// or add it. Returns whether a route was replaced.
+    #[napi]
+    pub fn replace_route(&self, route: Route) -> napi::Result<bool> {
+        replace_route(&self.router, self.with_defaults(route))
+    }
+
+    /// Every registered route, ordered by path and then method.
+    #[napi]
+    pub fn routes(&self) -> Vec<RouteInfoCore> {
+        self.router.routes().iter().map(|route| route.info()).collect()
+    }
+
+    #[napi]
+    pub fn route_count(&self) -> RouteCountCore {
+        let routes = self.router.routes();
+        let static_routes =
+            routes.iter().filter(|route| HttpRouter::is_static_route(&route.path)).count();
+
+        RouteCountCore {
+            total: routes.len() as u32,
+            static_routes: static_routes as u32,
+            dynamic_routes: (routes.len() - static_routes) as u32,
+        }
     }
 
-    /// Start the HTTP server on TCP or Unix socket and execute the `ready` callback if provided.
+    /// Replace every route at once. The new table is built first and published in a
+    /// single step; if any route is invalid the current routes stay in place.
+    #[napi]
+    pub fn swap_routes(&self, routes: Vec<Route>) -> napi::Result<()> {
+        let routes = routes.into_iter().map(|route| self.with_defaults(route)).collect();
+        swap_routes(&self.router, routes)
+    }
+
+    /// Fill in per-route options left unset from the server options.
+    fn with_defaults(&self, mut route: Route) -> Route {
+        let routing = self.config.routing.as_ref();
+        route.case_insensitive =
+            route.case_insensitive.or_else(|| routing.and_then(|r| r.case_insensitive));
+        route
+    }
+
+    /// Start the HTTP(S) server (HTTP/1.1 and HTTP/2) on every configured TCP or Unix socket
+    /// listener and execute the `ready` callback if provided.
     ///
     /// # Safety
     ///
@@ -68,126 +187,177 @@ impl ServerCore {
     /// - `start` is not called twice simultaneously on the same instance.
     ///
     /// The caller must guarantee these conditions.
-    #[napi(ts_args_type = "ready: (() => void) | undefined")]
-    pub async unsafe fn start(&mut self, ready: Option<ThreadsafeFunction<()>>) {
-        let (shutdown_tx, mut shutdown_rx) = watch::channel::<()>(());
+    ///
+    /// Rejects (instead of aborting the process) when a listener cannot be opened;
+    /// the message starts with a code such as `EADDRINUSE`, `EACCES` or `ERR_INVALID_HOST`.
+    /// Either every listener is bound or none is.
+    #[napi(ts_args_type = "ready: ((addresses: ServerAddressCore[]) => void) | undefined")]
+    pub async unsafe fn start(&mut self, ready: Option<ReadyCallback>) -> napi::Result<()> {
+        let (shutdown_tx, shutdown_rx) = watch::channel(Shutdown::Running);
         self.shutdown_tx = Some(shutdown_tx);
 
-        #[cfg(unix)]
-        if let Some(ref socket_path) = self.config.unix_socket {
-            self.start_unix_socket(socket_path, ready, &mut shutdown_rx).await;
-            return;
-        }
-
-        self.start_tcp(ready, &mut shutdown_rx).await;
+        let result = self.serve(ready, shutdown_rx).await;
+        self.addresses.lock().clear();
+        result
     }
 
-    async fn start_tcp(
+    async fn serve(
         &self,
-        ready: Option<ThreadsafeFunction<()>>,
-        shutdown_rx: &mut watch::Receiver<()>,
-    ) {
-        let port = self.config.port.unwrap_or(3000);
-        let host = self.config.host.as_deref().unwrap_or("0.0.0.0");
-        let addr: SocketAddr = format!("{host}:{port}").parse().expect("Invalid address");
+        ready: Option<ReadyCallback>,
+        shutdown_rx: watch::Receiver<Shutdown>,
+    ) -> napi::Result<()> {
+        let cors = self
+            .config
+            .cors
+            .as_ref()
+            .map(CorsPolicy::new)
+            .transpose()
+            .map_err(napi::Error::from_reason)?
+            .map(Arc::new);
+
+        let mut listeners = Vec::new();
+        for spec in listener_specs(&self.config)? {
+            match BoundListener::bind(&spec, &self.config).await {
+                Ok(listener) => listeners.push(listener),
+                Err(err) => {
+                    release_listeners(listeners, &self.config);
+                    return Err(err);
+                }
+            }
+        }
 
-        let listener = create_reusable_listener(addr).await.expect("Failed to create listener");
+        // Routes registered so far were built in place; from now on changes are copied.
+        self.router.publish();
+        let ctx = self.serve_context(cors, &shutdown_rx);
 
-        if let Some(ready_cb) = ready {
-            ready_cb.call(Ok(()), ThreadsafeFunctionCallMode::NonBlocking);
-        }
+        self.notify_ready(listeners.iter().map(|l| l.address.clone()).collect(), ready);
+
+        let slots = accept_slots(&self.config);
+        join_all(listeners.into_iter().map(|listener| {
+            self.accept_loop(listener, ctx.clone(), slots.clone(), shutdown_rx.clone())
+        }))
+        .await;
+
+        Ok(())
+    }
 
+    async fn accept_loop(
+        &self,
+        listener: BoundListener,
+        ctx: ServeContext,
+        slots: Option<Arc<Semaphore>>,
+        mut shutdown_rx: watch::Receiver<Shutdown>,
+    ) {
         loop {
+            let accept = async {
+                // The semaphore is never closed, so acquiring only waits.
+                let slot = match &slots {
+                    Some(slots) => slots.clone().acquire_owned().await.ok(),
+                    None => None,
+                };
+                listener.accept().await.map(|accepted| (accepted, slot))
+            };
+
             tokio::select! {
-                Ok((tcp, remote_addr)) = listener.accept() => {
-                    let io = TokioIo::new(tcp);
-                    let config = self.config.clone();
-
-                    tokio::spawn(async move {
-                        if let Err(err) = http1::Builder::new()
-                            .serve_connection(io, hyper::service::service_fn(move |req| {
-                                handle_request(req, config.clone(), Some(remote_addr))
-                            }))
-                            .await
-                        {
-                            eprintln!("Error serving connection: {err:?}");
-                        }
-                    });
+                Ok(((stream, conn), slot)) = accept => {
+                    tokio::spawn(serve_stream(
+                        stream,
+                        listener.tls.clone(),
+                        listener.proxy_protocol,
+                        ctx.clone(),
+                        conn,
+                        self.connections.track_in(slot),
+                    ));
                 },
                 _ = shutdown_rx.changed() => break,
             }
         }
     }
 
-    #[cfg(unix)]
-    async fn start_unix_socket(
-        &self,
-        socket_path: &str,
-        ready: Option<ThreadsafeFunction<()>>,
-        shutdown_rx: &mut watch::Receiver<()>,
-    ) {
-        let path = Path::new(socket_path);
-
-        if path.exists() {
-            fs::remove_file(path).unwrap_or_else(|e| {
-                eprintln!("Warning: Could not remove existing socket file: {e}");
-            });
-        }
-
-        let listener = UnixListener::bind(path).expect("Failed to bind Unix socket");
+    fn notify_ready(&self, addresses: Vec<ServerAddressCore>, ready: Option<ReadyCallback>) {
+        *self.addresses.lock() = addresses.clone();
 
         if let Some(ready_cb) = ready {
-            ready_cb.call(Ok(()), ThreadsafeFunctionCallMode::NonBlocking);
+            ready_cb.call(addresses, ThreadsafeFunctionCallMode::NonBlocking);
         }
+    }
 
-        loop {
-            tokio::select! {
-                Ok((stream, _)) = listener.accept() => {
-                    let io = TokioIo::new(stream);
-                    let config = self.config.clone();
-
-                    tokio::spawn(async move {
-                        if let Err(err) = http1::Builder::new()
-                            .serve_connection(io, hyper::service::service_fn(move |req| {
-                                handle_request(req, config.clone(), None)
-                            }))
-                            .await
-                        {
-                            eprintln!("Error serving connection: {err:?}");
-                        }
-                    });
-                },
-                _ = shutdown_rx.changed() => break,
-            }
-        }
+    /// Addresses the server is currently listening on. Empty until `start` has bound them.
+    #[napi]
+    pub fn addresses(&self) -> Vec<ServerAddressCore> {
+        self.addresses.lock().clone()
+    }
 
-        let _ = fs::remove_file(path);
+    fn serve_context(
+        &self,
+        cors: Option<Arc<CorsPolicy>>,
+        shutdown_rx: &watch::Receiver<Shutdown>,
+    ) -> ServeContext {
+        ServeContext {
+            builder: Arc::new(build_connection_builder(&self.config)),
+            router: self.router.clone(),
+            // The listeners, and their certificates and keys, are only needed to bind.
+            config: Arc::new(ServerOptionsCore {
+                tls: None,
+                listeners: None,
+                ..self.config.clone()
+            }),
+            cors,
+            shutdown: shutdown_rx.clone(),
+        }
     }
 
+    /// Stop accepting connections and drop the open ones immediately.
     #[napi]
     pub fn close(&self) {
         if let Some(tx) = &self.shutdown_tx {
-            let _ = tx.send(());
+            let _ = tx.send(Shutdown::Immediate);
         }
     }
-}
 
-async fn create_reusable_listener(addr: SocketAddr) -> std::io::Result<TcpListener> {
-    use socket2::{Domain, Protocol, Socket, Type};
-    use std::net::TcpListener as StdListener;
+    /// Stop accepting connections and let open ones finish their in-flight requests.
+    ///
+    /// Resolves once every connection has closed. Connections still open after
+    /// `timeout` milliseconds (default: `shutdown_timeout` from the config) are dropped.
+    #[napi]
+    pub async fn shutdown(&self, timeout: Option<u32>) {
+        let Some(tx) = &self.shutdown_tx else {
+            return;
+        };
 
-    let domain = if addr.is_ipv4() { Domain::IPV4 } else { Domain::IPV6 };
-    let socket = Socket::new(domain, Type::STREAM, Some(Protocol::TCP))?;
+        let _ = tx.send(Shutdown::Graceful);
 
-    socket.set_reuse_address(true)?;
+        let timeout =
+            timeout.or(self.config.shutdown_timeout).unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT_MS);
+        let drained = tokio::time::timeout(
+            Duration::from_millis(timeout as u64),
+            self.connections.wait_idle(),
+        )
+        .await;
 
-    #[cfg(all(unix, not(target_os = "solaris"), not(target_os = "illumos")))]
-    socket.set_reuse_port(true)?;
+        if drained.is_err() {
+            let _ = tx.send(Shutdown::Immediate);
+            self.connections.wait_idle().await;
+        }
+    }
 
-    socket.set_nonblocking(true)?;
-    socket.bind(&addr.into())?;
-    socket.listen(1024)?;
+    #[napi]
+    pub fn active_connections(&self) -> u32 {
+        self.connections.active() as u32
+    }
+
+    #[napi]
+    pub fn connection_stats(&self) -> ConnectionStatsCore {
+        self.connections.stats()
+    }
+
+    /// Number of open connections from `ip`.
+    #[napi]
+    pub fn connections_from(&self, ip: String) -> napi::Result<u32> {
+        let ip: IpAddr = ip.parse().map_err(|_| {
+            napi::Error::new(napi::Status::InvalidArg, format!("Invalid IP '{ip}'"))
+        })?;
 
-    let std_listener: StdListener = socket.into();
-    TcpListener::from_std(std_listener)
+        Ok(self.connections.active_from(ip) as u32)
+    }
 }
@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod tests {
+    use super::super::core::*;
+    use super::super::params::RoutePattern;
+    use super::super::routes::{CompiledRoute, ResponseStrategy};
+    use http_body_util::Full;
+    use hyper::{Response, body::Bytes};
+
+    fn route(path: &str) -> CompiledRoute {
+        let pattern = RoutePattern::parse(path).unwrap();
+        CompiledRoute {
+            method: "GET".into(),
+            original_path: path.into(),
+            path: pattern.path.into(),
+            shape: pattern.shape.into(),
+            params: pattern.params.into(),
+            segments: Box::new([]),
+            strategy: ResponseStrategy::FullStatic(Response::new(Full::new(Bytes::new()))),
+            schema: None,
+            max_request_size: None,
+            timeout: None,
+            case_insensitive: false,
+            host: None,
+        }
+    }
+
+    #[test]
+    fn test_each_server_has_its_own_routes() {
+        let api = ServerCore::new(ServerOptionsCore::default());
+        let admin = ServerCore::new(ServerOptionsCore::default());
+
+        api.router.insert("GET", route("/users")).unwrap();
+        admin.router.insert("GET", route("/users")).unwrap();
+        admin.router.insert("GET", route("/stats")).unwrap();
+
+        assert_eq!(api.route_count().total, 1);
+        assert_eq!(admin.route_count().total, 2);
+
+        assert!(!api.remove_route("GET".into(), "/stats".into(), None));
+        assert!(api.remove_route("GET".into(), "/users".into(), None));
+        assert_eq!(api.route_count().total, 0);
+
+        let paths: Vec<_> = admin.routes().into_iter().map(|route| route.path).collect();
+        assert_eq!(paths, ["/stats", "/users"]);
+    }
+}
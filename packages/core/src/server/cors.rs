@@ -0,0 +1,189 @@
+use hyper::{
+    HeaderMap, Method, Response,
+    header::{
+        ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS,
+        ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_EXPOSE_HEADERS,
+        ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_HEADERS, ACCESS_CONTROL_REQUEST_METHOD,
+        ALLOW, HeaderValue, ORIGIN, VARY,
+    },
+};
+use napi_derive::napi;
+use regex::Regex;
+
+use crate::http::response::BoxedBody;
+
+/// Cross-origin resource sharing, handled without calling into JavaScript.
+#[derive(Clone, Default)]
+#[napi(object)]
+pub struct CorsOptionsCore {
+    /// Exact origins, `*` for any origin, or patterns such as `https://*.example.com`.
+    /// Default: any origin.
+    pub origins: Option<Vec<String>>,
+    /// Methods allowed in preflights. Default: the methods routed for the path.
+    pub methods: Option<Vec<String>>,
+    /// Request headers allowed in preflights. Default: whatever the preflight asks for.
+    pub allowed_headers: Option<Vec<String>>,
+    /// Response headers exposed to the calling script.
+    pub exposed_headers: Option<Vec<String>>,
+    /// Send `Access-Control-Allow-Credentials: true`. Requires `origins` listing the
+    /// allowed origins, without `*`. Default: false.
+    pub credentials: Option<bool>,
+    /// Seconds browsers may cache a preflight response.
+    pub max_age: Option<u32>,
+}
+
+/// Compiled form of `CorsOptionsCore`, built once per `start`.
+pub struct CorsPolicy {
+    any_origin: bool,
+    origins: Vec<String>,
+    patterns: Vec<Regex>,
+    methods: Option<HeaderValue>,
+    allowed_headers: Option<HeaderValue>,
+    exposed_headers: Option<HeaderValue>,
+    credentials: bool,
+    max_age: Option<HeaderValue>,
+}
+
+impl CorsPolicy {
+    /// Fails with a message naming the offending option. Credentials need an explicit
+    /// list of origins: allowing them from any origin would let every site read
+    /// responses made with the user's cookies.
+    pub fn new(options: &CorsOptionsCore) -> Result<Self, String> {
+        let mut policy = CorsPolicy {
+            any_origin: options.origins.is_none(),
+            origins: Vec::new(),
+            patterns: Vec::new(),
+            methods: header_list("methods", options.methods.as_deref())?,
+            allowed_headers: header_list("allowedHeaders", options.allowed_headers.as_deref())?,
+            exposed_headers: header_list("exposedHeaders", options.exposed_headers.as_deref())?,
+            credentials: options.credentials.unwrap_or(false),
+            max_age: options.max_age.map(HeaderValue::from),
+        };
+
+        for origin in options.origins.iter().flatten() {
+            if origin == "*" {
+                policy.any_origin = true;
+            } else if origin.contains('*') {
+                policy.patterns.push(origin_pattern(origin)?);
+            } else {
+                policy.origins.push(origin.trim_end_matches('/').to_ascii_lowercase());
+            }
+        }
+
+        if policy.credentials && policy.any_origin {
+            return Err("Invalid cors.credentials: list the allowed cors.origins, \
+                        credentials cannot be allowed for any origin"
+                .to_string());
+        }
+
+        Ok(policy)
+    }
+
+    /// A preflight is an `OPTIONS` request carrying both `Origin` and
+    /// `Access-Control-Request-Method`.
+    pub fn is_preflight(method: &Method, headers: &HeaderMap) -> bool {
+        method == Method::OPTIONS
+            && headers.contains_key(ORIGIN)
+            && headers.contains_key(ACCESS_CONTROL_REQUEST_METHOD)
+    }
+
+    pub fn allows_origin(&self, origin: &str) -> bool {
+        if self.any_origin {
+            return true;
+        }
+
+        let origin = origin.to_ascii_lowercase();
+        self.origins.contains(&origin) || self.patterns.iter().any(|p| p.is_match(&origin))
+    }
+
+    /// `Access-Control-Allow-Origin` for `origin`: `*` when any origin is allowed, the
+    /// origin itself otherwise, or `None` when it is not allowed.
+    fn allow_origin(&self, origin: &HeaderValue) -> Option<HeaderValue> {
+        let origin_str = origin.to_str().ok()?;
+
+        if !self.allows_origin(origin_str) {
+            return None;
+        }
+
+        if self.any_origin { Some(HeaderValue::from_static("*")) } else { Some(origin.clone()) }
+    }
+
+    /// Answer a preflight for a path routed for `allowed` methods.
+    pub fn preflight(&self, headers: &HeaderMap, allowed: &[Box<str>]) -> Response<BoxedBody> {
+        let allowed = HeaderValue::from_str(&allowed.join(", ")).ok();
+        let mut response = super::handler::empty_response(204);
+        let response_headers = response.headers_mut();
+
+        if let Some(allowed) = &allowed {
+            response_headers.insert(ALLOW, allowed.clone());
+        }
+        response_headers.append(VARY, HeaderValue::from_static("Origin"));
+        response_headers.append(VARY, HeaderValue::from_static("Access-Control-Request-Method"));
+        response_headers.append(VARY, HeaderValue::from_static("Access-Control-Request-Headers"));
+
+        let Some(allow_origin) = headers.get(ORIGIN).and_then(|o| self.allow_origin(o)) else {
+            return response;
+        };
+
+        response_headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+        if let Some(methods) = self.methods.clone().or(allowed) {
+            response_headers.insert(ACCESS_CONTROL_ALLOW_METHODS, methods);
+        }
+
+        let requested_headers = headers.get(ACCESS_CONTROL_REQUEST_HEADERS).cloned();
+        if let Some(allowed_headers) = self.allowed_headers.clone().or(requested_headers) {
+            response_headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, allowed_headers);
+        }
+        if self.credentials {
+            response_headers
+                .insert(ACCESS_CONTROL_ALLOW_CREDENTIALS, HeaderValue::from_static("true"));
+        }
+        if let Some(max_age) = &self.max_age {
+            response_headers.insert(ACCESS_CONTROL_MAX_AGE, max_age.clone());
+        }
+
+        response
+    }
+
+    /// Add the CORS headers for a request from `origin` to an ordinary response.
+    /// Responses that already carry `Access-Control-Allow-Origin` are left as they are.
+    pub fn decorate(&self, origin: Option<&HeaderValue>, headers: &mut HeaderMap) {
+        if headers.contains_key(ACCESS_CONTROL_ALLOW_ORIGIN) {
+            return;
+        }
+
+        if !self.any_origin {
+            headers.append(VARY, HeaderValue::from_static("Origin"));
+        }
+
+        let Some(allow_origin) = origin.and_then(|o| self.allow_origin(o)) else {
+            return;
+        };
+
+        headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+        if self.credentials {
+            headers.insert(ACCESS_CONTROL_ALLOW_CREDENTIALS, HeaderValue::from_static("true"));
+        }
+        if let Some(exposed) = &self.exposed_headers {
+            headers.insert(ACCESS_CONTROL_EXPOSE_HEADERS, exposed.clone());
+        }
+    }
+}
+
+fn header_list(option: &str, values: Option<&[String]>) -> Result<Option<HeaderValue>, String> {
+    values
+        .map(|values| {
+            HeaderValue::from_str(&values.join(", "))
+                .map_err(|_| format!("Invalid cors.{option}: {values:?}"))
+        })
+        .transpose()
+}
+
+/// `https://*.example.com` → `^https://[^/]+\.example\.com$`.
+fn origin_pattern(origin: &str) -> Result<Regex, String> {
+    let escaped: Vec<String> =
+        origin.trim_end_matches('/').to_ascii_lowercase().split('*').map(regex::escape).collect();
+
+    Regex::new(&format!("^{}$", escaped.join("[^/]+")))
+        .map_err(|e| format!("Invalid cors origin '{origin}': {e}"))
+}
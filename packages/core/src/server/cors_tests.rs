@@ -0,0 +1,146 @@
+#[cfg(test)]
+mod tests {
+    use super::super::cors::*;
+    use hyper::{HeaderMap, Method, header::HeaderValue};
+
+    fn policy(origins: Option<&[&str]>, credentials: bool) -> CorsPolicy {
+        CorsPolicy::new(&CorsOptionsCore {
+            origins: origins.map(|o| o.iter().map(|s| s.to_string()).collect()),
+            exposed_headers: Some(vec!["X-Request-Id".to_string()]),
+            credentials: Some(credentials),
+            max_age: Some(600),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, HeaderValue::from_static(value));
+        }
+        map
+    }
+
+    #[test]
+    fn test_origin_matching() {
+        let cors = policy(Some(&["https://app.example.com", "https://*.example.org"]), false);
+
+        assert!(cors.allows_origin("https://app.example.com"));
+        assert!(cors.allows_origin("HTTPS://APP.EXAMPLE.COM"));
+        assert!(cors.allows_origin("https://eu.example.org"));
+        assert!(!cors.allows_origin("https://example.org"));
+        assert!(!cors.allows_origin("https://evil.com/.example.org"));
+        assert!(!cors.allows_origin("https://example.com"));
+        assert!(policy(None, false).allows_origin("https://anything.test"));
+    }
+
+    #[test]
+    fn test_invalid_header_list() {
+        let options = CorsOptionsCore {
+            allowed_headers: Some(vec!["X-Bad\nHeader".to_string()]),
+            ..Default::default()
+        };
+        assert!(CorsPolicy::new(&options).is_err());
+    }
+
+    #[test]
+    fn test_credentials_need_listed_origins() {
+        for origins in [None, Some(vec!["*".to_string()])] {
+            let options =
+                CorsOptionsCore { origins, credentials: Some(true), ..Default::default() };
+            let err = CorsPolicy::new(&options).err().unwrap();
+            assert!(err.starts_with("Invalid cors.credentials"), "{err}");
+        }
+
+        let options = CorsOptionsCore {
+            origins: Some(vec!["https://*.example.com".to_string()]),
+            credentials: Some(true),
+            ..Default::default()
+        };
+        assert!(CorsPolicy::new(&options).is_ok());
+    }
+
+    #[test]
+    fn test_is_preflight() {
+        let preflight = headers(&[
+            ("origin", "https://app.example.com"),
+            ("access-control-request-method", "PUT"),
+        ]);
+
+        assert!(CorsPolicy::is_preflight(&Method::OPTIONS, &preflight));
+        assert!(!CorsPolicy::is_preflight(&Method::GET, &preflight));
+        assert!(!CorsPolicy::is_preflight(
+            &Method::OPTIONS,
+            &headers(&[("origin", "https://app.example.com")])
+        ));
+    }
+
+    #[test]
+    fn test_preflight_response() {
+        let cors = policy(Some(&["https://app.example.com"]), true);
+        let request = headers(&[
+            ("origin", "https://app.example.com"),
+            ("access-control-request-method", "PUT"),
+            ("access-control-request-headers", "content-type"),
+        ]);
+
+        let allowed: Vec<Box<str>> = vec!["GET".into(), "HEAD".into(), "OPTIONS".into()];
+        let response = cors.preflight(&request, &allowed);
+        let h = response.headers();
+
+        assert_eq!(response.status(), 204);
+        assert_eq!(h["access-control-allow-origin"], "https://app.example.com");
+        assert_eq!(h["access-control-allow-methods"], "GET, HEAD, OPTIONS");
+        assert_eq!(h["access-control-allow-headers"], "content-type");
+        assert_eq!(h["access-control-allow-credentials"], "true");
+        assert_eq!(h["access-control-max-age"], "600");
+    }
+
+    #[test]
+    fn test_preflight_from_unknown_origin() {
+        let cors = policy(Some(&["https://app.example.com"]), false);
+        let request =
+            headers(&[("origin", "https://evil.com"), ("access-control-request-method", "PUT")]);
+
+        let response = cors.preflight(&request, &["GET".into()]);
+        assert_eq!(response.status(), 204);
+        assert!(!response.headers().contains_key("access-control-allow-origin"));
+    }
+
+    #[test]
+    fn test_preflight_with_unrepresentable_methods() {
+        let cors = policy(None, false);
+        let request = headers(&[
+            ("origin", "https://app.example.com"),
+            ("access-control-request-method", "PUT"),
+        ]);
+
+        let response = cors.preflight(&request, &["GET".into(), "BAD\nMETHOD".into()]);
+        assert_eq!(response.status(), 204);
+        assert!(!response.headers().contains_key("allow"));
+        assert!(!response.headers().contains_key("access-control-allow-methods"));
+        assert_eq!(response.headers()["access-control-allow-origin"], "*");
+    }
+
+    #[test]
+    fn test_decorate() {
+        let origin = HeaderValue::from_static("https://app.example.com");
+
+        let mut h = HeaderMap::new();
+        policy(None, false).decorate(Some(&origin), &mut h);
+        assert_eq!(h["access-control-allow-origin"], "*");
+        assert_eq!(h["access-control-expose-headers"], "X-Request-Id");
+        assert!(!h.contains_key("vary"));
+
+        let mut h = HeaderMap::new();
+        policy(Some(&["https://app.example.com"]), true).decorate(Some(&origin), &mut h);
+        assert_eq!(h["access-control-allow-origin"], "https://app.example.com");
+        assert_eq!(h["vary"], "Origin");
+        assert_eq!(h["access-control-allow-credentials"], "true");
+
+        let mut h = headers(&[("access-control-allow-origin", "https://other.test")]);
+        policy(None, false).decorate(Some(&origin), &mut h);
+        assert_eq!(h["access-control-allow-origin"], "https://other.test");
+    }
+}
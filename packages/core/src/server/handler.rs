@@ -1,52 +1,111 @@
 use http_body_util::{BodyExt, Full, StreamBody};
 use hyper::{
-    Request, Response,
-    body::{Bytes, Frame, Incoming},
-    header::{HeaderName, HeaderValue},
+    Method, Request, Response, StatusCode, Version,
+    body::{Body, Bytes, Frame, Incoming},
+    header::{
+        ALLOW, CONNECTION, CONTENT_LENGTH, EXPECT, HOST, HeaderName, HeaderValue, LOCATION, ORIGIN,
+    },
+    http::request::Parts,
 };
 
 use napi::{bindgen_prelude::External, threadsafe_function::ThreadsafeFunctionCallMode};
 use serde_json::json;
 
 use futures_util::stream;
-use std::{convert::Infallible, net::SocketAddr, sync::Arc};
-use tokio::sync::mpsc;
+use std::{convert::Infallible, sync::Arc, time::Duration};
+use tokio::{sync::mpsc, time::error::Elapsed};
 
 use crate::{
     http::{
-        request::RequestCore,
+        request::{BodyError, RequestCore},
         response::{BoxedBody, ResponseChannel, ResponseMessage},
     },
     server::{
+        connection::ConnectionInfo,
         context::ContextObject,
         core::ServerOptionsCore,
-        routes::{ROUTER, ResponseStrategy},
+        cors::CorsPolicy,
+        host::normalize_host,
+        path::{RoutingOptionsCore, TrailingSlash, decode_params, encode_path, normalize_path},
+        router::{GlobalRouter, RouteLookup},
+        routes::{CompiledRoute, ResponseStrategy},
     },
     validation::parser::*,
 };
 
 pub async fn handle_request(
     req: Request<Incoming>,
-    config: ServerOptionsCore,
-    remote_addr: Option<SocketAddr>,
+    router: Arc<GlobalRouter>,
+    config: Arc<ServerOptionsCore>,
+    conn: ConnectionInfo,
+    cors: Option<Arc<CorsPolicy>>,
+) -> Result<Response<BoxedBody>, std::convert::Infallible> {
+    let head = req.method() == Method::HEAD;
+    let origin = req.headers().get(ORIGIN).cloned();
+
+    let default_routing = RoutingOptionsCore::default();
+    let routing = config.routing.as_ref().unwrap_or(&default_routing);
+    let path = normalize_path(req.uri().path(), routing).into_owned();
+    let host = request_host(&req);
+
+    if let Some(cors) = &cors
+        && CorsPolicy::is_preflight(req.method(), req.headers())
+    {
+        let allowed = router.allowed_methods(host.as_deref(), &path);
+        if !allowed.is_empty() {
+            return Ok(cors.preflight(req.headers(), &allowed));
+        }
+    }
+
+    let mut response =
+        route_request(req, host.as_deref(), &path, routing, router, &config, conn).await?;
+
+    if let Some(cors) = &cors {
+        cors.decorate(origin.as_ref(), response.headers_mut());
+    }
+
+    Ok(if head { without_body(response) } else { response })
+}
+
+/// Host the request was sent to, from `Host` or the HTTP/2 `:authority`.
+fn request_host(req: &Request<Incoming>) -> Option<String> {
+    req.headers()
+        .get(HOST)
+        .and_then(|host| host.to_str().ok())
+        .or_else(|| req.uri().host())
+        .map(normalize_host)
+}
+
+async fn route_request(
+    req: Request<Incoming>,
+    host: Option<&str>,
+    path: &str,
+    routing: &RoutingOptionsCore,
+    router: Arc<GlobalRouter>,
+    config: &ServerOptionsCore,
+    conn: ConnectionInfo,
 ) -> Result<Response<BoxedBody>, std::convert::Infallible> {
     let method = req.method().to_string();
-    let pathname = req.uri().path().to_string();
-
-    let matched = match ROUTER.find(&method, &pathname) {
-        Some(m) => m,
-        None => {
-            return Ok(Response::builder()
-                .status(404)
-                .body(
-                    Full::new(Bytes::from_static(b"Not Found"))
-                        .map_err(|never| match never {})
-                        .boxed(),
-                )
-                .unwrap());
+    let trailing_slash = TrailingSlash::from_config(Some(routing));
+
+    let mut matched = match router.resolve(host, &method, path, trailing_slash) {
+        RouteLookup::Found(m) => m,
+        RouteLookup::Redirect(location) => {
+            return Ok(permanent_redirect(&encode_path(&location), req.uri().query()));
+        }
+        RouteLookup::MethodNotAllowed(allowed) if method == "OPTIONS" => {
+            return Ok(options_response(&allowed));
         }
+        RouteLookup::MethodNotAllowed(allowed) if config.method_not_allowed.unwrap_or(true) => {
+            return Ok(method_not_allowed(&allowed));
+        }
+        _ => return Ok(plain_response(404, "Not Found")),
     };
 
+    if routing.percent_decode.unwrap_or(false) {
+        decode_params(&mut matched.params);
+    }
+
     let route = matched.route;
 
     if let ResponseStrategy::FullStatic(ref response) = route.strategy {
@@ -80,131 +139,23 @@ pub async fn handle_request(
             .unwrap());
     }
 
-    let mut req_core =
-        match RequestCore::new(req, remote_addr, config.trust_proxy.unwrap_or(false)).await {
-            Ok(core) => core,
-            Err(e) => {
-                eprintln!("Error creating request: {e}");
-                return Ok(Response::builder()
-                    .status(400)
-                    .body(
-                        Full::new(Bytes::from_static(b"Bad Request"))
-                            .map_err(|never| match never {})
-                            .boxed(),
-                    )
-                    .unwrap());
-            }
-        };
-
-    req_core.params = matched.params.into_iter().collect();
-
-    if let Some(schema) = &route.schema {
-        if let Some(params_schema) = &schema.params
-            && let Err(e) = parse_params(&req_core.params, params_schema)
-        {
-            let error_msg = format!("Validation error in {}: {}", e.field, e.message);
-            return Ok(Response::builder()
-                .status(400)
-                .header("Content-Type", "application/json")
-                .body(
-                    Full::new(Bytes::from(
-                        json!({
-                            "error": "Validation Error",
-                            "message": error_msg
-                        })
-                        .to_string(),
-                    ))
-                    .map_err(|never| match never {})
-                    .boxed(),
-                )
-                .unwrap());
-        }
-
-        if let Some(query_schema) = &schema.query
-            && let Err(e) = parse_query(&req_core.query_raw, query_schema)
-        {
-            let error_msg = format!("Validation error in {}: {}", e.field, e.message);
-            return Ok(Response::builder()
-                .status(400)
-                .header("Content-Type", "application/json")
-                .body(
-                    Full::new(Bytes::from(
-                        json!({
-                            "error": "Validation Error",
-                            "message": error_msg
-                        })
-                        .to_string(),
-                    ))
-                    .map_err(|never| match never {})
-                    .boxed(),
-                )
-                .unwrap());
-        }
+    let trust_proxy = config.trust_proxy.unwrap_or(false);
 
-        if let Some(body_schema) = &schema.body {
-            let has_body_method = matches!(method.as_str(), "POST" | "PUT" | "PATCH" | "DELETE");
-
-            if has_body_method {
-                if req_core.body.is_empty() {
-                    let error_msg = "Request body is required".to_string();
-                    return Ok(Response::builder()
-                        .status(400)
-                        .header("Content-Type", "application/json")
-                        .body(
-                            Full::new(Bytes::from(
-                                json!({
-                                    "error": "Validation Error",
-                                    "message": error_msg
-                                })
-                                .to_string(),
-                            ))
-                            .map_err(|never| match never {})
-                            .boxed(),
-                        )
-                        .unwrap());
-                }
+    let (parts, body) = req.into_parts();
 
-                if let Err(e) = parse_body(req_core.body.as_ref(), body_schema) {
-                    let error_msg = format!("Validation error in {}: {}", e.field, e.message);
-                    return Ok(Response::builder()
-                        .status(400)
-                        .header("Content-Type", "application/json")
-                        .body(
-                            Full::new(Bytes::from(
-                                json!({
-                                    "error": "Validation Error",
-                                    "message": error_msg
-                                })
-                                .to_string(),
-                            ))
-                            .map_err(|never| match never {})
-                            .boxed(),
-                        )
-                        .unwrap());
-                }
-            }
-        }
+    let mut req_core = RequestCore::new(&parts, &conn, trust_proxy);
+    req_core.numeric_params = route
+        .params
+        .iter()
+        .filter_map(|param| {
+            let value = matched.params.get(&*param.name)?;
+            Some((param.name.to_string(), param.constraint.as_ref()?.to_number(value)?))
+        })
+        .collect();
+    req_core.params = matched.params.into_iter().collect();
 
-        if let Some(headers_schema) = &schema.headers
-            && let Err(e) = parse_headers(&req_core.headers_raw, headers_schema)
-        {
-            let error_msg = format!("Validation error in {}: {}", e.field, e.message);
-            return Ok(Response::builder()
-                .status(400)
-                .header("Content-Type", "application/json")
-                .body(
-                    Full::new(Bytes::from(
-                        json!({
-                            "error": "Validation Error",
-                            "message": error_msg
-                        })
-                        .to_string(),
-                    ))
-                    .map_err(|never| match never {})
-                    .boxed(),
-                )
-                .unwrap());
-        }
+    if let Some(response) = receive_request(&route, config, &parts, body, &mut req_core).await {
+        return Ok(response);
     }
 
     let (response_tx, mut response_rx) = mpsc::unbounded_channel();
@@ -215,11 +166,17 @@ pub async fn handle_request(
         res: External::new(res_builder.clone()),
     };
 
-    if let ResponseStrategy::Dynamic(handler) = route.strategy.clone() {
-        let _ = handler.call(ctx_obj, ThreadsafeFunctionCallMode::NonBlocking);
+    if let ResponseStrategy::Dynamic(handler) = route.strategy.clone()
+        && handler.call(ctx_obj, ThreadsafeFunctionCallMode::NonBlocking) != napi::Status::Ok
+    {
+        return Ok(plain_response(503, "Service Unavailable"));
     }
 
-    if let Some(first_msg) = response_rx.recv().await {
+    let Ok(first_msg) = first_message(&route, config, &mut response_rx).await else {
+        return Ok(plain_response(504, "Gateway Timeout"));
+    };
+
+    if let Some(first_msg) = first_msg {
         match first_msg {
             ResponseMessage::Complete { status, headers, body } => {
                 let mut response = Response::builder().status(status);
@@ -271,3 +228,211 @@ pub async fn handle_request(
         .body(Full::new(Bytes::new()).map_err(|never| match never {}).boxed())
         .unwrap())
 }
+
+/// The handler's first response message, waiting at most the route's `timeout` (or the
+/// server's). `None` when the handler dropped its response without sending anything.
+pub async fn first_message(
+    route: &CompiledRoute,
+    config: &ServerOptionsCore,
+    response_rx: &mut mpsc::UnboundedReceiver<ResponseMessage>,
+) -> Result<Option<ResponseMessage>, Elapsed> {
+    match route.timeout.or(config.timeout) {
+        Some(ms) => {
+            tokio::time::timeout(Duration::from_millis(ms as u64), response_rx.recv()).await
+        }
+        None => Ok(response_rx.recv().await),
+    }
+}
+
+/// Validate a request against `route` and receive its body into `req_core`. Returns the
+/// response rejecting the request, if any.
+///
+/// Everything that can be checked from the head is checked before the body is read.
+/// hyper only sends `100 Continue` once the body is polled, so clients that sent
+/// `Expect: 100-continue` are turned away without uploading anything.
+pub async fn receive_request<B>(
+    route: &CompiledRoute,
+    config: &ServerOptionsCore,
+    parts: &Parts,
+    body: B,
+    req_core: &mut RequestCore,
+) -> Option<Response<BoxedBody>>
+where
+    B: Body<Data = Bytes>,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    let body_limit = route.max_request_size.or(config.max_request_size).map(|l| l as usize);
+    let body_timeout = config.body_read_timeout.map(|ms| Duration::from_millis(ms as u64));
+
+    if let Some(schema) = &route.schema {
+        if let Some(params_schema) = &schema.params
+            && let Err(e) = parse_params(&req_core.params, params_schema)
+        {
+            return Some(validation_error(format!(
+                "Validation error in {}: {}",
+                e.field, e.message
+            )));
+        }
+
+        if let Some(query_schema) = &schema.query
+            && let Err(e) = parse_query(&req_core.query_raw, query_schema)
+        {
+            return Some(validation_error(format!(
+                "Validation error in {}: {}",
+                e.field, e.message
+            )));
+        }
+
+        if let Some(headers_schema) = &schema.headers
+            && let Err(e) = parse_headers(&req_core.headers_raw, headers_schema)
+        {
+            return Some(validation_error(format!(
+                "Validation error in {}: {}",
+                e.field, e.message
+            )));
+        }
+    }
+
+    if parts
+        .headers
+        .get(EXPECT)
+        .is_some_and(|expect| !expect.as_bytes().eq_ignore_ascii_case(b"100-continue"))
+    {
+        return Some(plain_response(417, "Expectation Failed"));
+    }
+
+    if let Some(limit) = body_limit
+        && declared_content_length(parts).is_some_and(|len| len > limit as u64)
+    {
+        return Some(plain_response(413, "Payload Too Large"));
+    }
+
+    match req_core.receive_body(body, body_limit, body_timeout).await {
+        Ok(()) => {}
+        Err(BodyError::TooLarge) => return Some(plain_response(413, "Payload Too Large")),
+        Err(BodyError::Timeout) => return Some(plain_response(408, "Request Timeout")),
+        Err(e) => {
+            eprintln!("Error reading request body: {e}");
+            return Some(plain_response(400, "Bad Request"));
+        }
+    }
+
+    if let Some(body_schema) = route.schema.as_ref().and_then(|s| s.body.as_ref())
+        && matches!(parts.method.as_str(), "POST" | "PUT" | "PATCH" | "DELETE")
+    {
+        if req_core.body.is_empty() {
+            return Some(validation_error("Request body is required".to_string()));
+        }
+
+        if let Err(e) = parse_body(req_core.body.as_ref(), body_schema) {
+            return Some(validation_error(format!(
+                "Validation error in {}: {}",
+                e.field, e.message
+            )));
+        }
+    }
+
+    None
+}
+
+fn declared_content_length(parts: &Parts) -> Option<u64> {
+    parts.headers.get(CONTENT_LENGTH)?.to_str().ok()?.trim().parse().ok()
+}
+
+fn method_not_allowed(allowed: &[Box<str>]) -> Response<BoxedBody> {
+    with_allow(plain_response(405, "Method Not Allowed"), allowed)
+}
+
+/// Automatic answer to `OPTIONS` for paths without an OPTIONS route.
+fn options_response(allowed: &[Box<str>]) -> Response<BoxedBody> {
+    with_allow(empty_response(204), allowed)
+}
+
+fn with_allow(mut response: Response<BoxedBody>, allowed: &[Box<str>]) -> Response<BoxedBody> {
+    if let Ok(value) = HeaderValue::from_str(&allowed.join(", ")) {
+        response.headers_mut().insert(ALLOW, value);
+    }
+
+    response
+}
+
+/// 308, so the method and body are kept when the client follows it.
+fn permanent_redirect(path: &str, query: Option<&str>) -> Response<BoxedBody> {
+    let location = match query {
+        Some(query) => format!("{path}?{query}"),
+        None => path.to_string(),
+    };
+
+    let mut response = empty_response(308);
+    if let Ok(value) = HeaderValue::from_str(&location) {
+        response.headers_mut().insert(LOCATION, value);
+    }
+
+    response
+}
+
+fn validation_error(message: String) -> Response<BoxedBody> {
+    Response::builder()
+        .status(400)
+        .header("Content-Type", "application/json")
+        .body(
+            Full::new(Bytes::from(
+                json!({
+                    "error": "Validation Error",
+                    "message": message
+                })
+                .to_string(),
+            ))
+            .map_err(|never| match never {})
+            .boxed(),
+        )
+        .unwrap()
+}
+
+/// Response for connections turned away by a connection limit. HTTP/1 connections are
+/// closed after it.
+pub fn service_unavailable(version: Version) -> Response<BoxedBody> {
+    let mut response = plain_response(503, "Service Unavailable");
+
+    if version < Version::HTTP_2 {
+        response.headers_mut().insert(CONNECTION, HeaderValue::from_static("close"));
+    }
+
+    response
+}
+
+/// Answer a HEAD request with the headers the full response would carry. A body of
+/// known size keeps its `Content-Length`; a streamed one is dropped without one.
+fn without_body(response: Response<BoxedBody>) -> Response<BoxedBody> {
+    let (mut parts, body) = response.into_parts();
+
+    let bodiless = parts.status.is_informational()
+        || parts.status == StatusCode::NO_CONTENT
+        || parts.status == StatusCode::NOT_MODIFIED;
+
+    if let Some(len) = body.size_hint().exact()
+        && !bodiless
+        && !parts.headers.contains_key(CONTENT_LENGTH)
+    {
+        parts.headers.insert(CONTENT_LENGTH, HeaderValue::from(len));
+    }
+
+    Response::from_parts(parts, empty_body())
+}
+
+pub fn empty_response(status: u16) -> Response<BoxedBody> {
+    Response::builder().status(status).body(empty_body()).unwrap()
+}
+
+fn empty_body() -> BoxedBody {
+    Full::new(Bytes::new()).map_err(|never| match never {}).boxed()
+}
+
+fn plain_response(status: u16, body: &'static str) -> Response<BoxedBody> {
+    Response::builder()
+        .status(status)
+        .body(
+            Full::new(Bytes::from_static(body.as_bytes())).map_err(|never| match never {}).boxed(),
+        )
+        .unwrap()
+}
@@ -0,0 +1,179 @@
+#[cfg(test)]
+mod tests {
+    use super::super::connection::ConnectionInfo;
+    use super::super::core::ServerOptionsCore;
+    use super::super::handler::*;
+    use super::super::params::RoutePattern;
+    use super::super::routes::{CompiledRoute, ResponseStrategy, RouteSchema};
+    use crate::http::{request::RequestCore, response::ResponseMessage};
+    use crate::validation::types::SchemaType;
+    use http_body_util::Full;
+    use hyper::{
+        Request, Response,
+        body::{Body, Bytes, Frame},
+    };
+    use std::{
+        convert::Infallible,
+        pin::Pin,
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+        task::{Context, Poll},
+        time::Duration,
+    };
+    use tokio::sync::mpsc;
+
+    fn route(path: &str) -> CompiledRoute {
+        let pattern = RoutePattern::parse(path).unwrap();
+        CompiledRoute {
+            method: "POST".into(),
+            original_path: path.into(),
+            path: pattern.path.into(),
+            shape: pattern.shape.into(),
+            params: pattern.params.into(),
+            segments: Box::new([]),
+            strategy: ResponseStrategy::FullStatic(Response::new(Full::new(Bytes::new()))),
+            schema: None,
+            max_request_size: None,
+            timeout: None,
+            case_insensitive: false,
+            host: None,
+        }
+    }
+
+    /// A body that records whether the handler started reading it.
+    struct Tracked {
+        data: Option<Bytes>,
+        read: Arc<AtomicBool>,
+    }
+
+    impl Tracked {
+        fn new(data: &'static [u8]) -> (Self, Arc<AtomicBool>) {
+            let read = Arc::new(AtomicBool::new(false));
+            (Self { data: Some(Bytes::from_static(data)), read: read.clone() }, read)
+        }
+    }
+
+    impl Body for Tracked {
+        type Data = Bytes;
+        type Error = Infallible;
+
+        fn poll_frame(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Option<Result<Frame<Bytes>, Infallible>>> {
+            self.read.store(true, Ordering::SeqCst);
+            Poll::Ready(self.data.take().map(|data| Ok(Frame::data(data))))
+        }
+    }
+
+    /// Run `receive_request` for a request with `headers` and `body`, returning the
+    /// rejection status (if any) and whether the body was read.
+    async fn receive(
+        route: &CompiledRoute,
+        config: &ServerOptionsCore,
+        headers: &[(&str, &str)],
+        body: &'static [u8],
+    ) -> (Option<u16>, bool) {
+        let mut request = Request::post("/upload");
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let (parts, ()) = request.body(()).unwrap().into_parts();
+
+        let mut req_core = RequestCore::new(&parts, &ConnectionInfo::default(), false);
+        let (body, read) = Tracked::new(body);
+        let rejection = receive_request(route, config, &parts, body, &mut req_core).await;
+
+        (rejection.map(|r| r.status().as_u16()), read.load(Ordering::SeqCst))
+    }
+
+    #[tokio::test]
+    async fn test_route_limit_overrides_server_limit() {
+        let config = ServerOptionsCore { max_request_size: Some(1024), ..Default::default() };
+        let body = &[b'x'; 100];
+
+        let mut strict = route("/upload");
+        strict.max_request_size = Some(10);
+        assert_eq!(receive(&strict, &config, &[], body).await.0, Some(413));
+
+        let mut relaxed = route("/upload");
+        relaxed.max_request_size = Some(4096);
+        let config = ServerOptionsCore { max_request_size: Some(10), ..Default::default() };
+        assert_eq!(receive(&relaxed, &config, &[], body).await.0, None);
+        assert_eq!(receive(&route("/upload"), &config, &[], body).await.0, Some(413));
+    }
+
+    #[tokio::test]
+    async fn test_declared_length_is_rejected_before_reading() {
+        let mut limited = route("/upload");
+        limited.max_request_size = Some(10);
+
+        let headers = [("content-length", "100"), ("expect", "100-continue")];
+        let (status, read) =
+            receive(&limited, &ServerOptionsCore::default(), &headers, &[b'x'; 100]).await;
+
+        assert_eq!(status, Some(413));
+        assert!(!read);
+    }
+
+    #[tokio::test]
+    async fn test_schema_rejection_skips_the_body() {
+        let required = SchemaType::String { optional: false, default: None, constraints: vec![] };
+        let headers = SchemaType::Object {
+            optional: false,
+            default: None,
+            shape: [("x-api-key".to_string(), required)].into(),
+        };
+        let mut guarded = route("/upload");
+        guarded.schema =
+            Some(RouteSchema { params: None, query: None, body: None, headers: Some(headers) });
+
+        let config = ServerOptionsCore::default();
+        let (status, read) = receive(&guarded, &config, &[], b"{}").await;
+        assert_eq!(status, Some(400));
+        assert!(!read);
+
+        let (status, read) = receive(&guarded, &config, &[("x-api-key", "secret")], b"{}").await;
+        assert_eq!(status, None);
+        assert!(read);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_expectation_is_rejected_before_reading() {
+        let config = ServerOptionsCore::default();
+
+        let (status, read) =
+            receive(&route("/upload"), &config, &[("expect", "x-gzip")], b"x").await;
+        assert_eq!(status, Some(417));
+        assert!(!read);
+
+        let (status, read) =
+            receive(&route("/upload"), &config, &[("expect", "100-Continue")], b"x").await;
+        assert_eq!(status, None);
+        assert!(read);
+    }
+
+    #[tokio::test]
+    async fn test_route_timeout_overrides_server_timeout() {
+        let config = ServerOptionsCore { timeout: Some(60_000), ..Default::default() };
+        let mut quick = route("/upload");
+        quick.timeout = Some(20);
+
+        // The handler holds on to its response channel without ever answering.
+        let (_response_tx, mut response_rx) = mpsc::unbounded_channel();
+        let waited = tokio::time::timeout(
+            Duration::from_secs(5),
+            first_message(&quick, &config, &mut response_rx),
+        )
+        .await
+        .expect("the route timeout applies, not the server's");
+        assert!(waited.is_err());
+
+        let (response_tx, mut response_rx) = mpsc::unbounded_channel();
+        response_tx.send(ResponseMessage::StreamEnd).unwrap();
+        let answered = first_message(&quick, &config, &mut response_rx).await;
+        assert!(matches!(answered, Ok(Some(ResponseMessage::StreamEnd))));
+    }
+}
@@ -0,0 +1,109 @@
+/// Host a route is bound to: `api.example.com`, `*.example.com` (any subdomain), or
+/// `:tenant.example.com` (one label, captured as the `tenant` param).
+#[derive(Clone, Debug)]
+pub struct HostPattern {
+    /// The pattern lowercased, without a trailing dot. Routes with the same source share
+    /// a route table.
+    source: Box<str>,
+    /// Labels after the leading `*`, if any, in order.
+    labels: Vec<HostLabel>,
+    wildcard: bool,
+}
+
+#[derive(Clone, Debug)]
+enum HostLabel {
+    Literal(Box<str>),
+    Param(Box<str>),
+}
+
+impl HostPattern {
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let invalid = |reason: &str| format!("Invalid host pattern '{pattern}': {reason}");
+        let source = pattern.trim_end_matches('.').to_ascii_lowercase();
+
+        let (wildcard, rest) = match source.strip_prefix("*.") {
+            Some(rest) => (true, rest),
+            None => (false, source.as_str()),
+        };
+
+        let mut labels = Vec::new();
+        for label in rest.split('.') {
+            labels.push(match label.strip_prefix(':') {
+                Some("") => return Err(invalid("param without a name")),
+                Some(name) => HostLabel::Param(name.into()),
+                None if label.is_empty() => return Err(invalid("empty label")),
+                None if label.contains('*') => {
+                    return Err(invalid("'*' is only allowed as the first label"));
+                }
+                None if label.contains([':', '/']) => {
+                    return Err(invalid("expected a host name without port or path"));
+                }
+                None => HostLabel::Literal(label.into()),
+            });
+        }
+
+        Ok(HostPattern { source: source.into(), labels, wildcard })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Match a host normalized by `normalize_host`, returning the captured params.
+    pub fn matches(&self, host: &str) -> Option<Vec<(String, String)>> {
+        let host_labels: Vec<&str> = host.split('.').collect();
+
+        let extra = host_labels.len().checked_sub(self.labels.len())?;
+        if extra > 0 && !self.wildcard || extra == 0 && self.wildcard {
+            return None;
+        }
+
+        let mut params = Vec::new();
+        for (label, value) in self.labels.iter().zip(&host_labels[extra..]) {
+            match label {
+                HostLabel::Literal(literal) if **literal == **value => {}
+                HostLabel::Param(name) if !value.is_empty() => {
+                    params.push((name.to_string(), value.to_string()));
+                }
+                _ => return None,
+            }
+        }
+
+        Some(params)
+    }
+
+    pub fn param_names(&self) -> impl Iterator<Item = &str> {
+        self.labels.iter().filter_map(|label| match label {
+            HostLabel::Param(name) => Some(&**name),
+            HostLabel::Literal(_) => None,
+        })
+    }
+
+    /// Sort key putting more specific patterns first: exact hosts, then patterns with
+    /// params, then wildcards, each with more labels first.
+    pub fn specificity(&self) -> (u8, std::cmp::Reverse<usize>) {
+        let kind = if self.wildcard {
+            2
+        } else if self.param_names().next().is_some() {
+            1
+        } else {
+            0
+        };
+
+        (kind, std::cmp::Reverse(self.labels.len()))
+    }
+}
+
+/// The host of a `Host` header or `:authority`, lowercased, without port or trailing dot.
+pub fn normalize_host(host: &str) -> String {
+    let host = if host.starts_with('[') {
+        host.split_inclusive(']').next().unwrap_or(host)
+    } else {
+        match host.rsplit_once(':') {
+            Some((name, port)) if port.bytes().all(|b| b.is_ascii_digit()) => name,
+            _ => host,
+        }
+    };
+
+    host.trim_end_matches('.').to_ascii_lowercase()
+}
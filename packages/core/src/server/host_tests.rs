@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use super::super::host::*;
+
+    fn pattern(source: &str) -> HostPattern {
+        HostPattern::parse(source).unwrap()
+    }
+
+    #[test]
+    fn test_exact_host() {
+        let host = pattern("API.Example.com.");
+        assert_eq!(host.as_str(), "api.example.com");
+        assert_eq!(host.matches("api.example.com"), Some(vec![]));
+        assert_eq!(host.matches("www.example.com"), None);
+        assert_eq!(host.matches("eu.api.example.com"), None);
+    }
+
+    #[test]
+    fn test_wildcard_host() {
+        let host = pattern("*.example.com");
+        assert!(host.matches("app.example.com").is_some());
+        assert!(host.matches("eu.app.example.com").is_some());
+        assert!(host.matches("example.com").is_none());
+        assert!(host.matches("example.org").is_none());
+    }
+
+    #[test]
+    fn test_host_params() {
+        let host = pattern(":tenant.example.com");
+        assert_eq!(
+            host.matches("acme.example.com"),
+            Some(vec![("tenant".to_string(), "acme".to_string())])
+        );
+        assert_eq!(host.matches("eu.acme.example.com"), None);
+        assert_eq!(host.matches("example.com"), None);
+        assert_eq!(host.param_names().collect::<Vec<_>>(), ["tenant"]);
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        for source in ["", "api..example.com", "api.*.com", "example.com:8080", ":.example.com"] {
+            assert!(HostPattern::parse(source).is_err(), "{source}");
+        }
+    }
+
+    #[test]
+    fn test_specificity() {
+        let mut patterns =
+            [pattern("*.example.com"), pattern(":tenant.example.com"), pattern("api.example.com")];
+        patterns.sort_by_key(|p| p.specificity());
+
+        let order: Vec<_> = patterns.iter().map(|p| p.as_str()).collect();
+        assert_eq!(order, ["api.example.com", ":tenant.example.com", "*.example.com"]);
+    }
+
+    #[test]
+    fn test_normalize_host() {
+        assert_eq!(normalize_host("API.Example.com:8080"), "api.example.com");
+        assert_eq!(normalize_host("example.com."), "example.com");
+        assert_eq!(normalize_host("[::1]:3000"), "[::1]");
+        assert_eq!(normalize_host("localhost"), "localhost");
+    }
+}
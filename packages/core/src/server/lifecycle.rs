@@ -0,0 +1,173 @@
+use hyper::rt::{Read, ReadBufCursor, Write};
+
+use std::{
+    future::pending,
+    io,
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{sync::Notify, time::Instant};
+
+use crate::server::core::ServerOptionsCore;
+
+/// Per-connection bookkeeping for `keep_alive_timeout` and `max_requests_per_connection`:
+/// tells `serve_connection` when the connection should be wound down.
+pub struct ConnectionLifecycle {
+    started: Instant,
+    last_activity_ms: AtomicU64,
+    in_flight: AtomicUsize,
+    served: AtomicUsize,
+    max_requests: Option<usize>,
+    idle_timeout: Option<Duration>,
+    retire: Notify,
+}
+
+impl ConnectionLifecycle {
+    pub fn new(max_requests: Option<usize>, idle_timeout: Option<Duration>) -> Arc<Self> {
+        Arc::new(Self {
+            started: Instant::now(),
+            last_activity_ms: AtomicU64::new(0),
+            in_flight: AtomicUsize::new(0),
+            served: AtomicUsize::new(0),
+            max_requests,
+            idle_timeout,
+            retire: Notify::new(),
+        })
+    }
+
+    pub fn from_config(config: &ServerOptionsCore) -> Arc<Self> {
+        Self::new(
+            config.max_requests_per_connection.map(|max| max as usize),
+            config.keep_alive_timeout.map(|ms| Duration::from_millis(ms as u64)),
+        )
+    }
+
+    /// Record I/O on the connection.
+    pub fn touch(&self) {
+        let elapsed = self.started.elapsed().as_millis() as u64;
+        self.last_activity_ms.store(elapsed, Ordering::Relaxed);
+    }
+
+    /// Count a new request. The connection is retired once it has served `max_requests`;
+    /// the request itself is still answered.
+    pub fn begin_request(self: &Arc<Self>) -> RequestGuard {
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        let served = self.served.fetch_add(1, Ordering::AcqRel) + 1;
+
+        if self.max_requests.is_some_and(|max| served >= max) {
+            self.retire.notify_one();
+        }
+
+        RequestGuard { lifecycle: self.clone() }
+    }
+
+    pub fn served(&self) -> usize {
+        self.served.load(Ordering::Acquire)
+    }
+
+    /// Resolves once the connection has served its last request or sat idle (no I/O and
+    /// no request in progress) for the idle timeout.
+    pub async fn retired(&self) {
+        tokio::select! {
+            _ = self.retire.notified() => {}
+            _ = self.idle() => {}
+        }
+    }
+
+    async fn idle(&self) {
+        let Some(timeout) = self.idle_timeout else {
+            return pending().await;
+        };
+
+        loop {
+            let last = Duration::from_millis(self.last_activity_ms.load(Ordering::Relaxed));
+            let idle_for = self.started.elapsed().saturating_sub(last);
+            let busy = self.in_flight.load(Ordering::Acquire) > 0;
+
+            if !busy && idle_for >= timeout {
+                return;
+            }
+
+            let wait = if busy { timeout } else { timeout - idle_for };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+pub struct RequestGuard {
+    lifecycle: Arc<ConnectionLifecycle>,
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        self.lifecycle.touch();
+        self.lifecycle.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Connection I/O that reports activity to a `ConnectionLifecycle`.
+pub struct Tracked<I> {
+    inner: I,
+    lifecycle: Arc<ConnectionLifecycle>,
+}
+
+impl<I> Tracked<I> {
+    pub fn new(inner: I, lifecycle: Arc<ConnectionLifecycle>) -> Self {
+        Self { inner, lifecycle }
+    }
+
+    fn record<T>(&self, poll: Poll<T>) -> Poll<T> {
+        if poll.is_ready() {
+            self.lifecycle.touch();
+        }
+        poll
+    }
+}
+
+impl<I: Read + Unpin> Read for Tracked<I> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: ReadBufCursor<'_>,
+    ) -> Poll<io::Result<()>> {
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        self.record(poll)
+    }
+}
+
+impl<I: Write + Unpin> Write for Tracked<I> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_write(cx, buf);
+        self.record(poll)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_write_vectored(cx, bufs);
+        self.record(poll)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+    use super::super::lifecycle::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_retires_after_max_requests() {
+        let lifecycle = ConnectionLifecycle::new(Some(2), None);
+
+        drop(lifecycle.begin_request());
+        let retired = tokio::time::timeout(Duration::from_millis(20), lifecycle.retired()).await;
+        assert!(retired.is_err());
+
+        drop(lifecycle.begin_request());
+        tokio::time::timeout(Duration::from_millis(50), lifecycle.retired()).await.unwrap();
+        assert_eq!(lifecycle.served(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_never_retires_without_limits() {
+        let lifecycle = ConnectionLifecycle::new(None, None);
+        drop(lifecycle.begin_request());
+
+        let retired = tokio::time::timeout(Duration::from_millis(30), lifecycle.retired()).await;
+        assert!(retired.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retires_when_idle() {
+        let lifecycle = ConnectionLifecycle::new(None, Some(Duration::from_secs(5)));
+        lifecycle.touch();
+
+        let retired = tokio::time::timeout(Duration::from_secs(6), lifecycle.retired()).await;
+        assert!(retired.is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_request_in_progress_is_not_idle() {
+        let lifecycle = ConnectionLifecycle::new(None, Some(Duration::from_secs(5)));
+        let request = lifecycle.begin_request();
+
+        let retired = tokio::time::timeout(Duration::from_secs(20), lifecycle.retired()).await;
+        assert!(retired.is_err());
+
+        drop(request);
+        let retired = tokio::time::timeout(Duration::from_secs(20), lifecycle.retired()).await;
+        assert!(retired.is_ok());
+    }
+}
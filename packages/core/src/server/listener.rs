@@ -0,0 +1,776 @@
+use napi::{Error, Status};
+
+use socket2::{Domain, Protocol, SockRef, Socket, TcpKeepalive, Type};
+use tokio_rustls::TlsAcceptor;
+
+use std::{
+    fmt, io,
+    net::{IpAddr, SocketAddr, TcpListener as StdTcpListener},
+    ops::Range,
+    pin::Pin,
+    sync::{
+        OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::{TcpListener, TcpStream, lookup_host},
+};
+
+#[cfg(unix)]
+use socket2::SockAddr;
+#[cfg(unix)]
+use std::{
+    fs,
+    os::unix::net::UnixListener as StdUnixListener,
+    path::{Path, PathBuf},
+};
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+
+use crate::server::{
+    connection::{ConnectionInfo, PeerCredentialsCore, http2_enabled},
+    core::ServerOptionsCore,
+    tls::{TlsOptionsCore, build_tls_acceptor},
+};
+
+const DEFAULT_BACKLOG: i32 = 1024;
+
+/// Low-level socket tuning. Unset fields keep the operating system defaults.
+#[derive(Clone, Default)]
+#[napi(object)]
+pub struct SocketOptionsCore {
+    /// Maximum length of the pending connections queue. Default: 1024.
+    pub backlog: Option<u32>,
+    /// Set `TCP_NODELAY` on accepted connections.
+    pub no_delay: Option<bool>,
+    /// Idle time in milliseconds before TCP keep-alive probes start. Probes are off when unset.
+    pub keep_alive_time: Option<u32>,
+    /// Time in milliseconds between keep-alive probes.
+    pub keep_alive_interval: Option<u32>,
+    /// Unanswered probes before the connection is dropped.
+    pub keep_alive_retries: Option<u32>,
+    /// For IPv6 hosts: `true` accepts IPv6 only, `false` also accepts IPv4 (dual-stack).
+    pub ipv6_only: Option<bool>,
+    pub recv_buffer_size: Option<u32>,
+    pub send_buffer_size: Option<u32>,
+}
+
+/// One address to listen on: an inherited socket when `fd` is set, a Unix socket when
+/// `unix_socket` is set, TCP otherwise.
+#[derive(Clone, Default)]
+#[napi(object)]
+pub struct ListenerOptionsCore {
+    pub port: Option<u16>,
+    pub host: Option<String>,
+    /// Path of the Unix socket, or `@name` for a Linux abstract-namespace socket.
+    pub unix_socket: Option<String>,
+    /// Permission bits for the Unix socket file (e.g. `0o660`). Falls back to the server-wide
+    /// `unix_socket_mode`.
+    pub unix_socket_mode: Option<u32>,
+    /// Group name or numeric gid that should own the Unix socket file. Falls back to the
+    /// server-wide `unix_socket_group`.
+    pub unix_socket_group: Option<String>,
+    /// Adopt this already-open listening socket (e.g. held by a supervisor) instead of binding.
+    pub fd: Option<i32>,
+    /// TLS for this listener. TCP listeners fall back to the server-wide `tls`.
+    pub tls: Option<TlsOptionsCore>,
+    /// Expect a PROXY protocol (v1 or v2) header on every connection and take the client
+    /// address from it. Falls back to the server-wide `proxy_protocol`.
+    pub proxy_protocol: Option<bool>,
+}
+
+/// Address a listener is actually bound to, as reported to JavaScript.
+///
+/// TCP listeners fill `address`, `family` and `port` (the resolved port when `0` was
+/// requested); Unix socket listeners fill `path` only (`@name` for abstract sockets).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[napi(object)]
+pub struct ServerAddressCore {
+    pub address: Option<String>,
+    #[napi(ts_type = "'IPv4' | 'IPv6' | undefined")]
+    pub family: Option<String>,
+    pub port: Option<u16>,
+    pub path: Option<String>,
+}
+
+impl ServerAddressCore {
+    pub fn tcp(addr: SocketAddr) -> Self {
+        Self {
+            address: Some(addr.ip().to_string()),
+            family: Some(if addr.is_ipv4() { "IPv4" } else { "IPv6" }.to_string()),
+            port: Some(addr.port()),
+            path: None,
+        }
+    }
+
+    pub fn unix(path: impl Into<String>) -> Self {
+        Self { address: None, family: None, port: None, path: Some(path.into()) }
+    }
+}
+
+/// Reasons a listener could not be opened, surfaced to JavaScript as a rejected `start`.
+///
+/// The JS error message is prefixed with the error code (e.g. `EADDRINUSE: ...`)
+/// so the TypeScript layer can expose it as `error.code`.
+#[derive(Debug)]
+pub enum ListenError {
+    AddrInUse(String),
+    PermissionDenied(String),
+    AddrNotAvailable(String),
+    InvalidHost(String),
+    /// A file that is not a socket already exists at the Unix socket path.
+    NotASocket(String),
+    /// The sockets passed by systemd were adopted by an earlier `start`, and closed with it.
+    SocketsAdopted,
+    Io(String, io::Error),
+}
+
+impl ListenError {
+    pub fn from_io(target: impl Into<String>, err: io::Error) -> Self {
+        let target = target.into();
+
+        match err.kind() {
+            io::ErrorKind::AddrInUse => ListenError::AddrInUse(target),
+            io::ErrorKind::PermissionDenied => ListenError::PermissionDenied(target),
+            io::ErrorKind::AddrNotAvailable => ListenError::AddrNotAvailable(target),
+            _ => ListenError::Io(target, err),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ListenError::AddrInUse(_) => "EADDRINUSE",
+            ListenError::PermissionDenied(_) => "EACCES",
+            ListenError::AddrNotAvailable(_) => "EADDRNOTAVAIL",
+            ListenError::InvalidHost(_) => "ERR_INVALID_HOST",
+            ListenError::NotASocket(_) => "ENOTSOCK",
+            ListenError::SocketsAdopted => "ERR_SOCKET_ACTIVATION",
+            ListenError::Io(..) => "ERR_LISTEN",
+        }
+    }
+}
+
+impl fmt::Display for ListenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ListenError::AddrInUse(target) => write!(f, "address already in use {target}"),
+            ListenError::PermissionDenied(target) => write!(f, "permission denied {target}"),
+            ListenError::AddrNotAvailable(target) => write!(f, "address not available {target}"),
+            ListenError::InvalidHost(host) => write!(f, "invalid host '{host}'"),
+            ListenError::NotASocket(path) => {
+                write!(f, "refusing to replace '{path}': existing file is not a socket")
+            }
+            ListenError::SocketsAdopted => {
+                write!(f, "the sockets passed by systemd were already adopted by an earlier start")
+            }
+            ListenError::Io(target, err) => write!(f, "failed to listen on {target}: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ListenError {}
+
+impl From<ListenError> for Error {
+    fn from(err: ListenError) -> Self {
+        let status = match err {
+            ListenError::InvalidHost(_) => Status::InvalidArg,
+            _ => Status::GenericFailure,
+        };
+
+        Error::new(status, format!("{}: {err}", err.code()))
+    }
+}
+
+/// Resolve `host` (an IP literal or a hostname such as `localhost`) to a bindable address.
+pub async fn resolve_addr(host: &str, port: u16) -> Result<SocketAddr, ListenError> {
+    if let Ok(ip) = host.trim_matches(['[', ']']).parse::<IpAddr>() {
+        return Ok(SocketAddr::new(ip, port));
+    }
+
+    lookup_host((host, port))
+        .await
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .ok_or_else(|| ListenError::InvalidHost(host.to_string()))
+}
+
+#[cfg_attr(not(unix), allow(unused_variables))]
+pub fn create_tcp_listener(
+    addr: SocketAddr,
+    reuse_port: bool,
+    options: &SocketOptionsCore,
+) -> io::Result<TcpListener> {
+    let domain = if addr.is_ipv4() { Domain::IPV4 } else { Domain::IPV6 };
+    let socket = Socket::new(domain, Type::STREAM, Some(Protocol::TCP))?;
+
+    // On Windows SO_REUSEADDR lets another process bind the same port, so it is Unix-only here.
+    #[cfg(not(windows))]
+    socket.set_reuse_address(true)?;
+
+    // SO_REUSEPORT lets several processes share the port, so it is strictly opt-in.
+    #[cfg(all(unix, not(target_os = "solaris"), not(target_os = "illumos")))]
+    socket.set_reuse_port(reuse_port)?;
+
+    if addr.is_ipv6()
+        && let Some(only_v6) = options.ipv6_only
+    {
+        socket.set_only_v6(only_v6)?;
+    }
+
+    if let Some(size) = options.recv_buffer_size {
+        socket.set_recv_buffer_size(size as usize)?;
+    }
+
+    if let Some(size) = options.send_buffer_size {
+        socket.set_send_buffer_size(size as usize)?;
+    }
+
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(backlog(options))?;
+
+    let std_listener: StdTcpListener = socket.into();
+    TcpListener::from_std(std_listener)
+}
+
+/// Create a Unix socket listener. The file's mode and group are applied between `bind` and
+/// `listen`, so no client can connect before the permissions are in place.
+#[cfg(unix)]
+pub fn create_unix_listener(
+    path: &Path,
+    options: &SocketOptionsCore,
+    mode: Option<u32>,
+    gid: Option<u32>,
+) -> io::Result<UnixListener> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let socket = Socket::new(Domain::UNIX, Type::STREAM, None)?;
+
+    socket.set_nonblocking(true)?;
+    socket.bind(&SockAddr::unix(path)?)?;
+
+    let secured = (|| {
+        if let Some(mode) = mode {
+            fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+        }
+        if gid.is_some() {
+            std::os::unix::fs::chown(path, None, gid)?;
+        }
+        socket.listen(backlog(options))
+    })();
+
+    if let Err(err) = secured {
+        let _ = fs::remove_file(path);
+        return Err(err);
+    }
+
+    let std_listener: StdUnixListener = socket.into();
+    UnixListener::from_std(std_listener)
+}
+
+/// Apply per-connection options to a freshly accepted TCP stream.
+pub fn configure_stream(stream: &TcpStream, options: &SocketOptionsCore) -> io::Result<()> {
+    if let Some(no_delay) = options.no_delay {
+        stream.set_nodelay(no_delay)?;
+    }
+
+    if let Some(time) = options.keep_alive_time {
+        let mut keepalive = TcpKeepalive::new().with_time(Duration::from_millis(time as u64));
+
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "macos",
+            target_os = "freebsd",
+            target_os = "windows"
+        ))]
+        {
+            if let Some(interval) = options.keep_alive_interval {
+                keepalive = keepalive.with_interval(Duration::from_millis(interval as u64));
+            }
+
+            if let Some(retries) = options.keep_alive_retries {
+                keepalive = keepalive.with_retries(retries);
+            }
+        }
+
+        SockRef::from(stream).set_tcp_keepalive(&keepalive)?;
+    }
+
+    Ok(())
+}
+
+fn backlog(options: &SocketOptionsCore) -> i32 {
+    options.backlog.map_or(DEFAULT_BACKLOG, |b| b.min(i32::MAX as u32) as i32)
+}
+
+/// Set while the sockets passed by systemd belong to a server. They can only be adopted
+/// once: closing the server closes them.
+static SYSTEMD_FDS_CLAIMED: AtomicBool = AtomicBool::new(false);
+
+/// The listeners described by `config`: its `listeners` list plus any sockets passed by
+/// systemd when `socket_activation` is on, or the top-level `port`/`host`/`unix_socket`
+/// when neither yields a listener.
+///
+/// Claims the systemd sockets; fails if an earlier call already did, rather than falling
+/// back to the default address. `release_listeners` gives the claim back.
+pub fn listener_specs(config: &ServerOptionsCore) -> Result<Vec<ListenerOptionsCore>, ListenError> {
+    let mut specs = config.listeners.clone().unwrap_or_default();
+
+    if config.socket_activation.unwrap_or(false) {
+        let fds = claim_systemd_fds(systemd_listen_fds())?;
+        specs.extend(fds.map(|fd| ListenerOptionsCore { fd: Some(fd), ..Default::default() }));
+    }
+
+    if specs.is_empty() {
+        specs.push(ListenerOptionsCore {
+            port: config.port,
+            host: config.host.clone(),
+            unix_socket: config.unix_socket.clone(),
+            ..Default::default()
+        });
+    }
+
+    Ok(specs)
+}
+
+/// Undo binding the listeners of `config` after one of them failed: sockets bound here are
+/// closed, adopted ones are handed back open (see `BoundListener::release`), and the
+/// systemd sockets can be claimed again.
+pub fn release_listeners(listeners: Vec<BoundListener>, config: &ServerOptionsCore) {
+    listeners.into_iter().for_each(BoundListener::release);
+
+    if config.socket_activation.unwrap_or(false) {
+        release_systemd_fds(systemd_listen_fds());
+    }
+}
+
+/// Claim the systemd sockets `fds` for one server; fails if they are already claimed.
+pub fn claim_systemd_fds(fds: Range<i32>) -> Result<Range<i32>, ListenError> {
+    if !fds.is_empty() && SYSTEMD_FDS_CLAIMED.swap(true, Ordering::AcqRel) {
+        return Err(ListenError::SocketsAdopted);
+    }
+
+    Ok(fds)
+}
+
+/// Give back a claim taken by `claim_systemd_fds`, once the sockets are open but unused.
+pub fn release_systemd_fds(fds: Range<i32>) {
+    if !fds.is_empty() {
+        SYSTEMD_FDS_CLAIMED.store(false, Ordering::Release);
+    }
+}
+
+/// File descriptors handed over by systemd socket activation (`LISTEN_FDS` / `LISTEN_PID`),
+/// read once. The environment is left untouched: child processes have another pid, so
+/// `LISTEN_PID` already keeps them from adopting the sockets.
+fn systemd_listen_fds() -> Range<i32> {
+    static FDS: OnceLock<Range<i32>> = OnceLock::new();
+
+    FDS.get_or_init(|| {
+        let pid = std::env::var("LISTEN_PID").ok();
+        let fds = std::env::var("LISTEN_FDS").ok();
+        parse_listen_fds(pid.as_deref(), fds.as_deref(), std::process::id())
+    })
+    .clone()
+}
+
+/// The descriptors described by `LISTEN_PID` and `LISTEN_FDS`: none unless the sockets
+/// were passed to `own_pid`, otherwise `LISTEN_FDS` descriptors starting at 3.
+pub fn parse_listen_fds(pid: Option<&str>, fds: Option<&str>, own_pid: u32) -> Range<i32> {
+    const SD_LISTEN_FDS_START: i32 = 3;
+
+    let for_us = pid.and_then(|pid| pid.parse::<u32>().ok()).is_some_and(|pid| pid == own_pid);
+
+    let count = if for_us { fds.and_then(|n| n.parse::<i32>().ok()).unwrap_or(0) } else { 0 };
+
+    SD_LISTEN_FDS_START..SD_LISTEN_FDS_START + count.clamp(0, i32::MAX - SD_LISTEN_FDS_START)
+}
+
+pub enum Listener {
+    Tcp(TcpListener),
+    /// The socket file is set when it was created by us and must be removed on drop.
+    #[cfg(unix)]
+    Unix(UnixListener, Option<SocketFile>),
+}
+
+/// A Unix socket file created by `bind`, removed when its listener is dropped.
+#[cfg(unix)]
+pub struct SocketFile(PathBuf);
+
+#[cfg(unix)]
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// A listening socket ready to accept, with the TLS and socket settings of its spec.
+pub struct BoundListener {
+    pub listener: Listener,
+    pub tls: Option<TlsAcceptor>,
+    pub proxy_protocol: bool,
+    pub address: ServerAddressCore,
+    socket_options: SocketOptionsCore,
+    /// Adopted from an inherited descriptor rather than bound here.
+    inherited: bool,
+}
+
+impl BoundListener {
+    pub async fn bind(
+        spec: &ListenerOptionsCore,
+        config: &ServerOptionsCore,
+    ) -> napi::Result<Self> {
+        let socket_options = config.socket.clone().unwrap_or_default();
+
+        let (listener, address) = if let Some(fd) = spec.fd {
+            adopt_listener(fd, &socket_options)
+                .map_err(|e| ListenError::from_io(format!("fd {fd}"), e))?
+        } else if let Some(socket_path) = &spec.unix_socket {
+            let mode = spec.unix_socket_mode.or(config.unix_socket_mode);
+            let group = spec.unix_socket_group.as_ref().or(config.unix_socket_group.as_ref());
+            bind_unix_listener(socket_path, &socket_options, mode, group.map(String::as_str))?
+        } else {
+            let port = spec.port.unwrap_or(3000);
+            let host = spec.host.as_deref().unwrap_or("0.0.0.0");
+            let addr = resolve_addr(host, port).await?;
+
+            let listener =
+                create_tcp_listener(addr, config.reuse_port.unwrap_or(false), &socket_options)
+                    .map_err(|e| ListenError::from_io(addr.to_string(), e))?;
+            let local_addr =
+                listener.local_addr().map_err(|e| ListenError::from_io(addr.to_string(), e))?;
+
+            (Listener::Tcp(listener), ServerAddressCore::tcp(local_addr))
+        };
+
+        let proxy_protocol = spec.proxy_protocol.or(config.proxy_protocol).unwrap_or(false);
+        let inherited = spec.fd.is_some();
+        let mut bound =
+            Self { listener, tls: None, proxy_protocol, address, socket_options, inherited };
+
+        // Unix sockets are local, so they only use TLS when their own spec asks for it.
+        let tls_options = match bound.listener {
+            Listener::Tcp(_) => spec.tls.as_ref().or(config.tls.as_ref()),
+            #[cfg(unix)]
+            Listener::Unix(..) => spec.tls.as_ref(),
+        };
+
+        match tls_options.map(|tls| build_tls_acceptor(tls, http2_enabled(config))).transpose() {
+            Ok(tls) => bound.tls = tls,
+            Err(err) => {
+                bound.release();
+                return Err(err);
+            }
+        }
+
+        Ok(bound)
+    }
+
+    /// Close the listener, except that an adopted socket is let go of without closing it,
+    /// so its owner (or a retried `start`) can adopt the same descriptor again.
+    pub fn release(self) {
+        if !self.inherited {
+            return;
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::fd::IntoRawFd;
+
+            let released = match self.listener {
+                Listener::Tcp(listener) => listener.into_std().map(IntoRawFd::into_raw_fd),
+                Listener::Unix(listener, _) => listener.into_std().map(IntoRawFd::into_raw_fd),
+            };
+
+            if let Err(err) = released {
+                eprintln!("Failed to release inherited socket: {err}");
+            }
+        }
+    }
+
+    pub async fn accept(&self) -> io::Result<(Stream, ConnectionInfo)> {
+        match &self.listener {
+            Listener::Tcp(listener) => {
+                let (tcp, remote_addr) = listener.accept().await?;
+
+                if let Err(err) = configure_stream(&tcp, &self.socket_options) {
+                    eprintln!("Failed to configure connection socket: {err}");
+                }
+
+                Ok((
+                    Stream::Tcp(tcp),
+                    ConnectionInfo { remote_addr: Some(remote_addr), ..Default::default() },
+                ))
+            }
+            #[cfg(unix)]
+            Listener::Unix(listener, _) => {
+                let (stream, _) = listener.accept().await?;
+                let peer_credentials = stream.peer_cred().ok().map(|cred| PeerCredentialsCore {
+                    uid: cred.uid(),
+                    gid: cred.gid(),
+                    pid: cred.pid(),
+                });
+
+                Ok((
+                    Stream::Unix(stream),
+                    ConnectionInfo { peer_credentials, ..Default::default() },
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn bind_unix_listener(
+    socket_path: &str,
+    options: &SocketOptionsCore,
+    mode: Option<u32>,
+    group: Option<&str>,
+) -> napi::Result<(Listener, ServerAddressCore)> {
+    if let Some(name) = socket_path.strip_prefix('@') {
+        let listener = create_abstract_listener(name, options)
+            .map_err(|e| ListenError::from_io(socket_path, e))?;
+        return Ok((Listener::Unix(listener, None), ServerAddressCore::unix(socket_path)));
+    }
+
+    let path = Path::new(socket_path);
+    remove_stale_socket(path, socket_path)?;
+
+    let gid =
+        group.map(resolve_group).transpose().map_err(|e| ListenError::from_io(socket_path, e))?;
+    let listener = create_unix_listener(path, options, mode, gid)
+        .map_err(|e| ListenError::from_io(socket_path, e))?;
+
+    let file = SocketFile(path.to_path_buf());
+    Ok((Listener::Unix(listener, Some(file)), ServerAddressCore::unix(socket_path)))
+}
+
+#[cfg(not(unix))]
+fn bind_unix_listener(
+    socket_path: &str,
+    _options: &SocketOptionsCore,
+    _mode: Option<u32>,
+    _group: Option<&str>,
+) -> napi::Result<(Listener, ServerAddressCore)> {
+    let err = io::Error::new(io::ErrorKind::Unsupported, "Unix sockets are not supported");
+    Err(ListenError::Io(socket_path.to_string(), err).into())
+}
+
+/// Clear the way for a new socket at `path`, but only by removing a socket nobody is
+/// accepting on: live sockets and other kinds of files are left alone.
+#[cfg(unix)]
+fn remove_stale_socket(path: &Path, target: &str) -> Result<(), ListenError> {
+    use std::os::unix::{fs::FileTypeExt, net::UnixStream as StdUnixStream};
+
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(ListenError::from_io(target, e)),
+    };
+
+    if !metadata.file_type().is_socket() {
+        return Err(ListenError::NotASocket(target.to_string()));
+    }
+
+    if StdUnixStream::connect(path).is_ok() {
+        return Err(ListenError::AddrInUse(target.to_string()));
+    }
+
+    fs::remove_file(path).map_err(|e| ListenError::from_io(target, e))
+}
+
+/// Resolve a group name (or a numeric gid) to a gid.
+#[cfg(unix)]
+fn resolve_group(group: &str) -> io::Result<u32> {
+    if let Ok(gid) = group.parse::<u32>() {
+        return Ok(gid);
+    }
+
+    let unknown =
+        || io::Error::new(io::ErrorKind::InvalidInput, format!("unknown group '{group}'"));
+    let name = std::ffi::CString::new(group).map_err(|_| unknown())?;
+
+    let mut buf = vec![0 as libc::c_char; 1024];
+    loop {
+        let mut grp = std::mem::MaybeUninit::<libc::group>::uninit();
+        let mut result = std::ptr::null_mut();
+
+        // SAFETY: every pointer refers to live, correctly sized storage owned by this frame.
+        let rc = unsafe {
+            libc::getgrnam_r(
+                name.as_ptr(),
+                grp.as_mut_ptr(),
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut result,
+            )
+        };
+
+        match rc {
+            0 if result.is_null() => return Err(unknown()),
+            // SAFETY: a non-null `result` means `grp` was filled in.
+            0 => return Ok(unsafe { grp.assume_init() }.gr_gid),
+            libc::ERANGE if buf.len() < 1 << 20 => buf.resize(buf.len() * 2, 0),
+            rc => return Err(io::Error::from_raw_os_error(rc)),
+        }
+    }
+}
+
+/// Bind a Linux abstract-namespace socket. No file is created, so there is nothing to
+/// clean up and file permissions do not apply.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn create_abstract_listener(name: &str, options: &SocketOptionsCore) -> io::Result<UnixListener> {
+    let socket = Socket::new(Domain::UNIX, Type::STREAM, None)?;
+
+    socket.set_nonblocking(true)?;
+    socket.bind(&SockAddr::unix(format!("\0{name}"))?)?;
+    socket.listen(backlog(options))?;
+
+    let std_listener: StdUnixListener = socket.into();
+    UnixListener::from_std(std_listener)
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android"))))]
+fn create_abstract_listener(_name: &str, _options: &SocketOptionsCore) -> io::Result<UnixListener> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "abstract Unix sockets are only supported on Linux",
+    ))
+}
+
+/// Take ownership of an inherited listening socket (TCP or Unix) instead of binding a new one.
+/// The descriptor is only taken over once it is known to be a usable stream socket; on
+/// error it is left open for its owner.
+#[cfg(unix)]
+pub fn adopt_listener(
+    fd: i32,
+    options: &SocketOptionsCore,
+) -> io::Result<(Listener, ServerAddressCore)> {
+    use std::os::fd::{BorrowedFd, FromRawFd};
+
+    // SAFETY: `fcntl(F_GETFD)` only inspects the descriptor table.
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+    if flags == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // SAFETY: `fd` was just checked to be open, and is only borrowed for the checks below.
+    let borrowed = unsafe { BorrowedFd::borrow_raw(fd) };
+    let socket = SockRef::from(&borrowed);
+
+    let local = socket.local_addr()?;
+    if socket.r#type()? != Type::STREAM {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "not a stream socket"));
+    }
+    if local.as_socket().is_none() && !local.is_unix() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "unsupported socket family"));
+    }
+
+    socket.set_nonblocking(true)?;
+    socket.listen(backlog(options))?;
+
+    // Inherited descriptors usually lack CLOEXEC; don't leak them into child processes.
+    // SAFETY: `fd` is open.
+    unsafe { libc::fcntl(fd, libc::F_SETFD, flags | libc::FD_CLOEXEC) };
+
+    // SAFETY: `fd` is an open listening socket and, by contract with the supervisor, owned
+    // by this process from now on.
+    let socket = unsafe { Socket::from_raw_fd(fd) };
+
+    if let Some(addr) = local.as_socket() {
+        let listener = TcpListener::from_std(socket.into())?;
+        return Ok((Listener::Tcp(listener), ServerAddressCore::tcp(addr)));
+    }
+
+    let path = match (local.as_pathname(), local.as_abstract_namespace()) {
+        (Some(path), _) => path.to_string_lossy().into_owned(),
+        (None, Some(name)) => format!("@{}", String::from_utf8_lossy(name)),
+        (None, None) => String::new(),
+    };
+    let listener = UnixListener::from_std(socket.into())?;
+    Ok((Listener::Unix(listener, None), ServerAddressCore::unix(path)))
+}
+
+#[cfg(not(unix))]
+pub fn adopt_listener(
+    _fd: i32,
+    _options: &SocketOptionsCore,
+) -> io::Result<(Listener, ServerAddressCore)> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "adopting sockets is only supported on Unix"))
+}
+
+/// An accepted connection from any listener kind.
+pub enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Stream::Tcp(s) => Pin::new(s).poll_read(cx, buf),
+            #[cfg(unix)]
+            Stream::Unix(s) => Pin::new(s).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Stream::Tcp(s) => Pin::new(s).poll_write(cx, buf),
+            #[cfg(unix)]
+            Stream::Unix(s) => Pin::new(s).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Stream::Tcp(s) => Pin::new(s).poll_write_vectored(cx, bufs),
+            #[cfg(unix)]
+            Stream::Unix(s) => Pin::new(s).poll_write_vectored(cx, bufs),
+        }
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        match self {
+            Stream::Tcp(s) => s.is_write_vectored(),
+            #[cfg(unix)]
+            Stream::Unix(s) => s.is_write_vectored(),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Stream::Tcp(s) => Pin::new(s).poll_flush(cx),
+            #[cfg(unix)]
+            Stream::Unix(s) => Pin::new(s).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Stream::Tcp(s) => Pin::new(s).poll_shutdown(cx),
+            #[cfg(unix)]
+            Stream::Unix(s) => Pin::new(s).poll_shutdown(cx),
+        }
+    }
+}
@@ -0,0 +1,396 @@
+#[cfg(test)]
+mod tests {
+    use super::super::core::ServerOptionsCore;
+    use super::super::listener::*;
+    use socket2::SockRef;
+    use std::{io, net::TcpListener as StdTcpListener};
+    use tokio::net::TcpStream;
+
+    #[test]
+    fn test_parse_listen_fds() {
+        assert_eq!(parse_listen_fds(Some("42"), Some("2"), 42), 3..5);
+        assert_eq!(parse_listen_fds(Some("42"), Some("2"), 7), 3..3);
+        assert_eq!(parse_listen_fds(None, Some("2"), 42), 3..3);
+        assert_eq!(parse_listen_fds(Some("42"), None, 42), 3..3);
+        assert_eq!(parse_listen_fds(Some("42"), Some("-1"), 42), 3..3);
+        assert_eq!(parse_listen_fds(Some("abc"), Some("1"), 42), 3..3);
+    }
+
+    #[test]
+    fn test_listener_specs() {
+        let config = ServerOptionsCore {
+            port: Some(8080),
+            host: Some("127.0.0.1".to_string()),
+            ..Default::default()
+        };
+        let specs = listener_specs(&config).unwrap();
+        assert_eq!(specs.len(), 1);
+        assert_eq!((specs[0].port, specs[0].host.as_deref()), (Some(8080), Some("127.0.0.1")));
+
+        let config = ServerOptionsCore {
+            port: Some(8080),
+            listeners: Some(vec![
+                ListenerOptionsCore { port: Some(80), ..Default::default() },
+                ListenerOptionsCore {
+                    unix_socket: Some("/tmp/app.sock".into()),
+                    ..Default::default()
+                },
+            ]),
+            ..Default::default()
+        };
+        let specs = listener_specs(&config).unwrap();
+        assert_eq!(specs.len(), 2);
+        assert_eq!(specs[0].port, Some(80));
+        assert_eq!(specs[1].unix_socket.as_deref(), Some("/tmp/app.sock"));
+    }
+
+    #[tokio::test]
+    async fn test_each_listener_is_bound_with_its_own_settings() {
+        let config = ServerOptionsCore {
+            proxy_protocol: Some(true),
+            listeners: Some(vec![
+                ListenerOptionsCore {
+                    port: Some(0),
+                    host: Some("127.0.0.1".to_string()),
+                    ..Default::default()
+                },
+                ListenerOptionsCore {
+                    port: Some(0),
+                    host: Some("127.0.0.1".to_string()),
+                    proxy_protocol: Some(false),
+                    ..Default::default()
+                },
+            ]),
+            ..Default::default()
+        };
+
+        let mut bound = Vec::new();
+        for spec in listener_specs(&config).unwrap() {
+            bound.push(BoundListener::bind(&spec, &config).await.unwrap());
+        }
+
+        assert_ne!(bound[0].address.port, bound[1].address.port);
+        assert!(bound[0].proxy_protocol);
+        assert!(!bound[1].proxy_protocol);
+    }
+
+    #[test]
+    fn test_systemd_sockets_are_claimed_once() {
+        assert_eq!(claim_systemd_fds(3..5).unwrap(), 3..5);
+
+        let err = claim_systemd_fds(3..5).unwrap_err();
+        assert_eq!(err.code(), "ERR_SOCKET_ACTIVATION");
+        // Without sockets from systemd there is nothing to claim.
+        assert_eq!(claim_systemd_fds(3..3).unwrap(), 3..3);
+
+        release_systemd_fds(3..5);
+        assert_eq!(claim_systemd_fds(3..5).unwrap(), 3..5);
+        release_systemd_fds(3..5);
+    }
+
+    #[tokio::test]
+    async fn test_listen_error_codes() {
+        let taken = StdTcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = taken.local_addr().unwrap();
+
+        let err = create_tcp_listener(addr, false, &SocketOptionsCore::default()).unwrap_err();
+        let err = ListenError::from_io(addr.to_string(), err);
+        assert_eq!(err.code(), "EADDRINUSE");
+        assert!(napi::Error::from(err).reason.starts_with("EADDRINUSE: "));
+
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        assert_eq!(ListenError::from_io("0.0.0.0:80", denied).code(), "EACCES");
+        let unavailable = io::Error::from(io::ErrorKind::AddrNotAvailable);
+        assert_eq!(ListenError::from_io("10.255.0.1:80", unavailable).code(), "EADDRNOTAVAIL");
+        let other = io::Error::other("boom");
+        assert_eq!(ListenError::from_io("0.0.0.0:80", other).code(), "ERR_LISTEN");
+
+        let err = resolve_addr("not a host", 3000).await.unwrap_err();
+        assert_eq!(err.code(), "ERR_INVALID_HOST");
+        assert_eq!(napi::Error::from(err).status, napi::Status::InvalidArg);
+    }
+
+    #[tokio::test]
+    async fn test_socket_options_are_applied() {
+        let options = SocketOptionsCore {
+            no_delay: Some(true),
+            keep_alive_time: Some(30_000),
+            recv_buffer_size: Some(64 * 1024),
+            ..Default::default()
+        };
+
+        let listener = create_tcp_listener(([127, 0, 0, 1], 0).into(), true, &options).unwrap();
+        let socket = SockRef::from(&listener);
+        #[cfg(unix)]
+        assert!(socket.reuse_port().unwrap());
+        assert!(socket.recv_buffer_size().unwrap() >= 64 * 1024);
+
+        let addr = listener.local_addr().unwrap();
+        let _client = TcpStream::connect(addr).await.unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+        assert!(!stream.nodelay().unwrap());
+
+        configure_stream(&stream, &options).unwrap();
+        assert!(stream.nodelay().unwrap());
+        assert!(SockRef::from(&stream).keepalive().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_reuse_port_is_opt_in() {
+        let options = SocketOptionsCore::default();
+        let listener = create_tcp_listener(([127, 0, 0, 1], 0).into(), false, &options).unwrap();
+
+        #[cfg(unix)]
+        assert!(!SockRef::from(&listener).reuse_port().unwrap());
+        let addr = listener.local_addr().unwrap();
+        assert!(create_tcp_listener(addr, true, &options).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bound_address_reports_the_assigned_port() {
+        let spec = ListenerOptionsCore {
+            port: Some(0),
+            host: Some("localhost".to_string()),
+            ..Default::default()
+        };
+        let bound = BoundListener::bind(&spec, &ServerOptionsCore::default()).await.unwrap();
+        let Listener::Tcp(listener) = &bound.listener else { panic!("expected a TCP listener") };
+
+        let local = listener.local_addr().unwrap();
+        assert_ne!(local.port(), 0);
+        assert_eq!(bound.address, ServerAddressCore::tcp(local));
+        assert_eq!(bound.address.path, None);
+    }
+
+    #[tokio::test]
+    async fn test_tcp_connections_have_no_peer_credentials() {
+        let spec = ListenerOptionsCore {
+            port: Some(0),
+            host: Some("127.0.0.1".to_string()),
+            ..Default::default()
+        };
+        let bound = BoundListener::bind(&spec, &ServerOptionsCore::default()).await.unwrap();
+        let port = bound.address.port.unwrap();
+
+        let _client = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        let (_, conn) = bound.accept().await.unwrap();
+        assert!(conn.peer_credentials.is_none());
+    }
+
+    #[cfg(unix)]
+    mod unix {
+        use super::super::super::tls::{Pem, TlsOptionsCore};
+        use super::*;
+        use std::os::fd::{AsRawFd, IntoRawFd};
+
+        fn is_open(fd: i32) -> bool {
+            // SAFETY: `fcntl(F_GETFD)` only inspects the descriptor table.
+            unsafe { libc::fcntl(fd, libc::F_GETFD) != -1 }
+        }
+
+        #[tokio::test]
+        async fn test_adopt_tcp_listener() {
+            let std_listener = StdTcpListener::bind("127.0.0.1:0").unwrap();
+            let port = std_listener.local_addr().unwrap().port();
+            let fd = std_listener.into_raw_fd();
+
+            let (listener, address) = adopt_listener(fd, &SocketOptionsCore::default()).unwrap();
+            assert!(matches!(listener, Listener::Tcp(_)));
+            assert_eq!(address, ServerAddressCore::tcp(([127, 0, 0, 1], port).into()));
+
+            // SAFETY: `fcntl(F_GETFD)` only inspects the descriptor table.
+            let flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+            assert_ne!(flags & libc::FD_CLOEXEC, 0);
+        }
+
+        #[tokio::test]
+        async fn test_adopt_unix_listener() {
+            let dir = std::env::temp_dir().join(format!("kito-adopt-{}", std::process::id()));
+            let _ = std::fs::remove_file(&dir);
+            let std_listener = std::os::unix::net::UnixListener::bind(&dir).unwrap();
+
+            let (listener, address) =
+                adopt_listener(std_listener.into_raw_fd(), &SocketOptionsCore::default()).unwrap();
+            assert!(matches!(listener, Listener::Unix(_, None)));
+            assert_eq!(address.path.as_deref(), dir.to_str());
+
+            let _ = std::fs::remove_file(&dir);
+        }
+
+        #[tokio::test]
+        async fn test_bound_unix_address_reports_the_path() {
+            let path = std::env::temp_dir().join(format!("kito-address-{}", std::process::id()));
+            let spec = ListenerOptionsCore {
+                unix_socket: Some(path.to_str().unwrap().to_string()),
+                ..Default::default()
+            };
+
+            let bound = BoundListener::bind(&spec, &ServerOptionsCore::default()).await.unwrap();
+            assert_eq!(bound.address, ServerAddressCore::unix(path.to_str().unwrap()));
+
+            drop(bound);
+            assert!(!path.exists());
+        }
+
+        #[tokio::test]
+        async fn test_unix_connections_carry_peer_credentials() {
+            let path = std::env::temp_dir().join(format!("kito-peer-{}", std::process::id()));
+            let spec = ListenerOptionsCore {
+                unix_socket: Some(path.to_str().unwrap().to_string()),
+                ..Default::default()
+            };
+            let bound = BoundListener::bind(&spec, &ServerOptionsCore::default()).await.unwrap();
+
+            let _client = tokio::net::UnixStream::connect(&path).await.unwrap();
+            let (_, conn) = bound.accept().await.unwrap();
+
+            let credentials = conn.peer_credentials.expect("peer credentials");
+            // SAFETY: `getuid` and `getgid` cannot fail.
+            let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+            assert_eq!((credentials.uid, credentials.gid), (uid, gid));
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            assert_eq!(credentials.pid, Some(std::process::id() as i32));
+            assert_eq!(conn.remote_addr, None);
+        }
+
+        fn socket_path(name: &str) -> String {
+            let path = std::env::temp_dir().join(format!("kito-{name}-{}", std::process::id()));
+            let _ = std::fs::remove_file(&path);
+            path.to_str().unwrap().to_string()
+        }
+
+        fn unix_spec(path: &str) -> ListenerOptionsCore {
+            ListenerOptionsCore { unix_socket: Some(path.to_string()), ..Default::default() }
+        }
+
+        /// Name of the group `gid`, from the group database.
+        fn group_name(gid: u32) -> String {
+            let mut buf = vec![0 as libc::c_char; 16 * 1024];
+            let mut grp = std::mem::MaybeUninit::<libc::group>::uninit();
+            let mut result = std::ptr::null_mut();
+
+            // SAFETY: every pointer refers to live, correctly sized storage owned by this frame.
+            let rc = unsafe {
+                libc::getgrgid_r(gid, grp.as_mut_ptr(), buf.as_mut_ptr(), buf.len(), &mut result)
+            };
+            assert!(rc == 0 && !result.is_null(), "gid {gid} has no name");
+
+            // SAFETY: a non-null `result` means `grp` was filled in, pointing into `buf`.
+            let name = unsafe { std::ffi::CStr::from_ptr(grp.assume_init().gr_name) };
+            name.to_str().unwrap().to_string()
+        }
+
+        #[tokio::test]
+        async fn test_unix_socket_mode() {
+            use std::os::unix::fs::PermissionsExt;
+
+            let path = socket_path("mode");
+            let config = ServerOptionsCore { unix_socket_mode: Some(0o660), ..Default::default() };
+
+            let bound = BoundListener::bind(&unix_spec(&path), &config).await.unwrap();
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o660);
+            drop(bound);
+
+            let spec = ListenerOptionsCore { unix_socket_mode: Some(0o600), ..unix_spec(&path) };
+            let bound = BoundListener::bind(&spec, &config).await.unwrap();
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+            drop(bound);
+        }
+
+        #[tokio::test]
+        async fn test_unix_socket_group() {
+            use std::os::unix::fs::MetadataExt;
+
+            // SAFETY: `getgid` cannot fail.
+            let gid = unsafe { libc::getgid() };
+            let path = socket_path("group");
+
+            for group in [group_name(gid), gid.to_string()] {
+                let spec =
+                    ListenerOptionsCore { unix_socket_group: Some(group), ..unix_spec(&path) };
+                let bound =
+                    BoundListener::bind(&spec, &ServerOptionsCore::default()).await.unwrap();
+                assert_eq!(std::fs::metadata(&path).unwrap().gid(), gid);
+                drop(bound);
+            }
+
+            let spec = ListenerOptionsCore {
+                unix_socket_group: Some("kito-no-such-group".to_string()),
+                ..unix_spec(&path)
+            };
+            let err = BoundListener::bind(&spec, &ServerOptionsCore::default()).await.err();
+            assert!(err.unwrap().reason.contains("unknown group 'kito-no-such-group'"));
+            assert!(!std::path::Path::new(&path).exists());
+        }
+
+        #[tokio::test]
+        async fn test_regular_file_is_not_replaced() {
+            let path = socket_path("file");
+            std::fs::write(&path, "keep me").unwrap();
+
+            let err = BoundListener::bind(&unix_spec(&path), &ServerOptionsCore::default()).await;
+            assert!(err.err().unwrap().reason.starts_with("ENOTSOCK: "));
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+
+            std::fs::remove_file(&path).unwrap();
+        }
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        #[tokio::test]
+        async fn test_abstract_socket() {
+            use std::os::linux::net::SocketAddrExt;
+            use std::os::unix::net::{SocketAddr, UnixStream};
+
+            let name = format!("kito-abstract-{}", std::process::id());
+            let spec = unix_spec(&format!("@{name}"));
+            let bound = BoundListener::bind(&spec, &ServerOptionsCore::default()).await.unwrap();
+            assert_eq!(bound.address, ServerAddressCore::unix(format!("@{name}")));
+            assert!(matches!(bound.listener, Listener::Unix(_, None)));
+
+            let addr = SocketAddr::from_abstract_name(name.as_bytes()).unwrap();
+            let _client = UnixStream::connect_addr(&addr).unwrap();
+            let (_, conn) = bound.accept().await.unwrap();
+            assert!(conn.peer_credentials.is_some());
+        }
+
+        #[tokio::test]
+        async fn test_rejected_descriptors_stay_open() {
+            let udp = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+            let file = std::fs::File::open("/dev/null").unwrap();
+
+            for fd in [udp.as_raw_fd(), file.as_raw_fd()] {
+                assert!(adopt_listener(fd, &SocketOptionsCore::default()).is_err());
+                assert!(is_open(fd), "fd {fd} was closed");
+            }
+
+            assert!(adopt_listener(-1, &SocketOptionsCore::default()).is_err());
+        }
+
+        #[tokio::test]
+        async fn test_adopted_sockets_stay_open_after_a_failed_start() {
+            let fd = StdTcpListener::bind("127.0.0.1:0").unwrap().into_raw_fd();
+            let spec = ListenerOptionsCore { fd: Some(fd), ..Default::default() };
+
+            let bound = BoundListener::bind(&spec, &ServerOptionsCore::default()).await.unwrap();
+            release_listeners(vec![bound], &ServerOptionsCore::default());
+            assert!(is_open(fd), "fd {fd} was closed");
+
+            let config = ServerOptionsCore {
+                tls: Some(TlsOptionsCore {
+                    cert: Some(Pem(b"not a certificate".to_vec())),
+                    key: Some(Pem(b"not a key".to_vec())),
+                    cert_path: None,
+                    key_path: None,
+                }),
+                ..Default::default()
+            };
+            assert!(BoundListener::bind(&spec, &config).await.is_err());
+            assert!(is_open(fd), "fd {fd} was closed");
+
+            let bound = BoundListener::bind(&spec, &ServerOptionsCore::default()).await.unwrap();
+            assert!(matches!(bound.listener, Listener::Tcp(_)));
+        }
+    }
+}
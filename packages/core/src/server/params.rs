@@ -0,0 +1,236 @@
+use regex::Regex;
+
+/// JavaScript numbers represent integers exactly up to 2^53 - 1.
+const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+/// What a path param must look like for its route to match, from `:id<int>` or
+/// `:id(\d+)`. Requests whose value does not fit fall through to other routes.
+#[derive(Clone, Debug)]
+pub enum ParamConstraint {
+    /// An integer JavaScript can represent exactly; also delivered as a number.
+    Int,
+    /// A decimal number such as `-1.5` or `2e3`; also delivered as a number.
+    Float,
+    /// A hyphenated UUID of any version.
+    Uuid,
+    /// ASCII letters only.
+    Alpha,
+    /// ASCII letters and digits only.
+    Alnum,
+    /// A regular expression the whole value must match.
+    Pattern(Regex),
+}
+
+impl ParamConstraint {
+    /// The constraint for `<name>`.
+    pub fn named(name: &str) -> Result<Self, String> {
+        match name {
+            "int" => Ok(ParamConstraint::Int),
+            "float" => Ok(ParamConstraint::Float),
+            "uuid" => Ok(ParamConstraint::Uuid),
+            "alpha" => Ok(ParamConstraint::Alpha),
+            "alnum" => Ok(ParamConstraint::Alnum),
+            _ => Err(format!(
+                "unknown param type <{name}>, expected int, float, uuid, alpha or alnum"
+            )),
+        }
+    }
+
+    /// The constraint for `(source)`, anchored at both ends.
+    pub fn pattern(source: &str) -> Result<Self, String> {
+        Regex::new(&format!("^(?:{source})$"))
+            .map(ParamConstraint::Pattern)
+            .map_err(|e| format!("invalid param pattern ({source}): {e}"))
+    }
+
+    pub fn matches(&self, value: &str) -> bool {
+        match self {
+            ParamConstraint::Int => value
+                .parse::<i64>()
+                .is_ok_and(|n| (-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(&n)),
+            ParamConstraint::Float => {
+                value.bytes().all(|b| b.is_ascii_digit() || b"+-.eE".contains(&b))
+                    && value.parse::<f64>().is_ok_and(f64::is_finite)
+            }
+            ParamConstraint::Uuid => {
+                value.len() == 36
+                    && value.bytes().enumerate().all(|(i, b)| match i {
+                        8 | 13 | 18 | 23 => b == b'-',
+                        _ => b.is_ascii_hexdigit(),
+                    })
+            }
+            ParamConstraint::Alpha => {
+                !value.is_empty() && value.bytes().all(|b| b.is_ascii_alphabetic())
+            }
+            ParamConstraint::Alnum => {
+                !value.is_empty() && value.bytes().all(|b| b.is_ascii_alphanumeric())
+            }
+            ParamConstraint::Pattern(regex) => regex.is_match(value),
+        }
+    }
+
+    /// `value` as a number, for the constraints whose params are delivered typed.
+    pub fn to_number(&self, value: &str) -> Option<f64> {
+        match self {
+            ParamConstraint::Int | ParamConstraint::Float => value.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for ParamConstraint {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ParamConstraint::Pattern(a), ParamConstraint::Pattern(b)) => a.as_str() == b.as_str(),
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PathParam {
+    pub name: Box<str>,
+    pub constraint: Option<ParamConstraint>,
+}
+
+/// A route path split into what matchit matches and what is checked afterwards.
+#[derive(Debug)]
+pub struct RoutePattern {
+    /// Matchit syntax with the route's param names: `/users/:id<int>` → `/users/{id}`.
+    pub path: String,
+    /// Matchit syntax with positional param names: `/users/{0}`. Routes differing only
+    /// in param names and constraints share a shape, and so a node of the trie.
+    pub shape: String,
+    /// One entry per param, in path order.
+    pub params: Vec<PathParam>,
+}
+
+impl RoutePattern {
+    /// Parse a path in Express (`/users/:id`, optionally `:id<type>` or `:id(regex)`) or
+    /// matchit (`/users/{id}`, `/{*rest}`) syntax. A constrained param must end its segment.
+    pub fn parse(path: &str) -> Result<Self, String> {
+        let invalid = |reason: String| format!("Invalid route path '{path}': {reason}");
+        let bytes = path.as_bytes();
+        let mut pattern =
+            RoutePattern { path: String::new(), shape: String::new(), params: Vec::new() };
+        let mut i = 0;
+
+        while i < bytes.len() {
+            match bytes[i] {
+                b':' if i == 0 || bytes[i - 1] == b'/' => {
+                    let end = path[i + 1..].find(['/', '(', '<']).map_or(path.len(), |n| i + 1 + n);
+                    let name = &path[i + 1..end];
+                    if name.is_empty() {
+                        return Err(invalid("param without a name".to_string()));
+                    }
+
+                    let (constraint, next) = match bytes.get(end) {
+                        Some(b'(') => {
+                            let close = closing_paren(path, end)
+                                .ok_or_else(|| invalid(format!("unclosed pattern for :{name}")))?;
+                            (
+                                ParamConstraint::pattern(&path[end + 1..close]).map_err(invalid)?,
+                                close + 1,
+                            )
+                        }
+                        Some(b'<') => {
+                            let close = path[end..]
+                                .find('>')
+                                .map(|n| end + n)
+                                .ok_or_else(|| invalid(format!("unclosed type for :{name}")))?;
+                            (
+                                ParamConstraint::named(&path[end + 1..close]).map_err(invalid)?,
+                                close + 1,
+                            )
+                        }
+                        _ => {
+                            pattern.push_param(name, false, None);
+                            i = end;
+                            continue;
+                        }
+                    };
+
+                    if bytes.get(next).is_some_and(|&b| b != b'/') {
+                        return Err(invalid(format!(":{name} must end its segment")));
+                    }
+
+                    pattern.push_param(name, false, Some(constraint));
+                    i = next;
+                }
+                b'{' | b'}' if bytes.get(i + 1) == Some(&bytes[i]) => {
+                    pattern.push_literal(&path[i..i + 2]);
+                    i += 2;
+                }
+                b'{' => {
+                    let close = path[i..]
+                        .find('}')
+                        .map(|n| i + n)
+                        .ok_or_else(|| invalid("unclosed '{'".to_string()))?;
+                    let name = &path[i + 1..close];
+
+                    match name.strip_prefix('*') {
+                        Some(name) => pattern.push_param(name, true, None),
+                        None => pattern.push_param(name, false, None),
+                    }
+                    i = close + 1;
+                }
+                _ => {
+                    let len = path[i..].chars().next().map_or(1, char::len_utf8);
+                    pattern.push_literal(&path[i..i + len]);
+                    i += len;
+                }
+            }
+        }
+
+        Ok(pattern)
+    }
+
+    fn push_literal(&mut self, literal: &str) {
+        self.path.push_str(literal);
+        self.shape.push_str(literal);
+    }
+
+    fn push_param(&mut self, name: &str, wildcard: bool, constraint: Option<ParamConstraint>) {
+        let star = if wildcard { "*" } else { "" };
+
+        self.path.push_str(&format!("{{{star}{name}}}"));
+        self.shape.push_str(&format!("{{{star}{}}}", self.params.len()));
+        self.params.push(PathParam { name: name.into(), constraint });
+    }
+}
+
+/// Whether two routes of the same shape would accept exactly the same requests.
+pub fn same_constraints(a: &[PathParam], b: &[PathParam]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.constraint == b.constraint)
+}
+
+/// Whether `values`, in path order, satisfy every constraint of `params`.
+pub fn satisfies(params: &[PathParam], values: &[&str]) -> bool {
+    params
+        .iter()
+        .zip(values)
+        .all(|(param, value)| param.constraint.as_ref().is_none_or(|c| c.matches(value)))
+}
+
+/// Index of the `)` closing the `(` at `open`, skipping escaped and nested parentheses.
+fn closing_paren(path: &str, open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut escaped = false;
+
+    for (i, c) in path[open..].char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + i);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
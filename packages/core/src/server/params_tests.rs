@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+    use super::super::params::*;
+
+    fn constraint(pattern: &RoutePattern, index: usize) -> Option<&ParamConstraint> {
+        pattern.params[index].constraint.as_ref()
+    }
+
+    #[test]
+    fn test_parse_express_and_matchit_syntax() {
+        let pattern = RoutePattern::parse("/users/:userId/files/{*rest}").unwrap();
+        assert_eq!(pattern.path, "/users/{userId}/files/{*rest}");
+        assert_eq!(pattern.shape, "/users/{0}/files/{*1}");
+        assert_eq!(&*pattern.params[0].name, "userId");
+        assert_eq!(&*pattern.params[1].name, "rest");
+
+        let pattern = RoutePattern::parse("/static/{{braces}}/a:b").unwrap();
+        assert_eq!(pattern.path, "/static/{{braces}}/a:b");
+        assert!(pattern.params.is_empty());
+    }
+
+    #[test]
+    fn test_parse_constraints() {
+        let pattern = RoutePattern::parse("/posts/:id<int>/:slug([a-z-]+)/:tag").unwrap();
+        assert_eq!(pattern.path, "/posts/{id}/{slug}/{tag}");
+        assert_eq!(pattern.shape, "/posts/{0}/{1}/{2}");
+        assert_eq!(constraint(&pattern, 0), Some(&ParamConstraint::Int));
+        assert!(matches!(constraint(&pattern, 1), Some(ParamConstraint::Pattern(_))));
+        assert_eq!(constraint(&pattern, 2), None);
+
+        let pattern = RoutePattern::parse(r"/v/:version(\d+(\.\d+)?)").unwrap();
+        assert!(constraint(&pattern, 0).unwrap().matches("1.2"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for path in [
+            "/users/:id<number>",
+            "/users/:id(\\d+",
+            "/users/:id([)",
+            "/:id<int>.json",
+            "/:",
+            "/{id",
+        ] {
+            assert!(RoutePattern::parse(path).is_err(), "{path}");
+        }
+    }
+
+    #[test]
+    fn test_named_constraints() {
+        let int = ParamConstraint::named("int").unwrap();
+        assert!(int.matches("42") && int.matches("-7"));
+        assert!(!int.matches("4.2") && !int.matches("abc") && !int.matches(""));
+        assert!(!int.matches("9007199254740992"));
+        assert_eq!(int.to_number("42"), Some(42.0));
+
+        let float = ParamConstraint::named("float").unwrap();
+        assert!(float.matches("-1.5") && float.matches("2e3") && float.matches("7"));
+        assert!(!float.matches("inf") && !float.matches("NaN") && !float.matches("1.2.3"));
+
+        let uuid = ParamConstraint::named("uuid").unwrap();
+        assert!(uuid.matches("550e8400-e29b-41d4-a716-446655440000"));
+        assert!(!uuid.matches("550e8400e29b41d4a716446655440000"));
+        assert_eq!(uuid.to_number("550e8400-e29b-41d4-a716-446655440000"), None);
+
+        assert!(ParamConstraint::named("alpha").unwrap().matches("abcXYZ"));
+        assert!(!ParamConstraint::named("alpha").unwrap().matches("abc1"));
+        assert!(ParamConstraint::named("alnum").unwrap().matches("abc1"));
+        assert!(!ParamConstraint::named("alnum").unwrap().matches("abc-1"));
+    }
+
+    #[test]
+    fn test_pattern_is_anchored() {
+        let digits = ParamConstraint::pattern(r"\d+").unwrap();
+        assert!(digits.matches("123"));
+        assert!(!digits.matches("a123") && !digits.matches("123a"));
+
+        let either = ParamConstraint::pattern("new|edit").unwrap();
+        assert!(either.matches("edit") && !either.matches("newt"));
+    }
+
+    #[test]
+    fn test_same_constraints_and_satisfies() {
+        let by_id = RoutePattern::parse("/users/:id<int>").unwrap();
+        let by_num = RoutePattern::parse("/users/:n<int>").unwrap();
+        let by_name = RoutePattern::parse("/users/:name").unwrap();
+        let by_regex = RoutePattern::parse(r"/users/:id(\d+)").unwrap();
+
+        assert!(same_constraints(&by_id.params, &by_num.params));
+        assert!(!same_constraints(&by_id.params, &by_name.params));
+        assert!(!same_constraints(&by_id.params, &by_regex.params));
+
+        assert!(satisfies(&by_id.params, &["12"]));
+        assert!(!satisfies(&by_id.params, &["me"]));
+        assert!(satisfies(&by_name.params, &["me"]));
+    }
+
+    #[test]
+    fn test_shapes_are_valid_matchit_paths() {
+        let mut router = matchit::Router::new();
+        let pattern = RoutePattern::parse("/users/:id<int>/files/{*rest}").unwrap();
+        router.insert(pattern.shape, 1).unwrap();
+
+        let matched = router.at("/users/12/files/a/b").unwrap();
+        assert_eq!(matched.params.get("0"), Some("12"));
+        assert_eq!(matched.params.get("1"), Some("a/b"));
+    }
+}
@@ -0,0 +1,192 @@
+use ahash::AHashMap;
+use napi_derive::napi;
+
+use std::borrow::Cow;
+
+/// How request paths are cleaned up before they are matched against routes.
+/// Every option is off by default, so paths are matched exactly as received.
+#[derive(Clone, Default)]
+#[napi(object)]
+pub struct RoutingOptionsCore {
+    /// `strict` treats `/users` and `/users/` as different paths, `ignore` serves either
+    /// spelling from the route that exists, and `redirect` answers 308 pointing at it.
+    /// Default: `strict`.
+    #[napi(ts_type = "'strict' | 'ignore' | 'redirect'")]
+    pub trailing_slash: Option<String>,
+    /// Collapse runs of slashes (`//users` → `/users`).
+    pub merge_slashes: Option<bool>,
+    /// Resolve `.` and `..` segments (`/a/./b/../c` → `/a/c`).
+    pub remove_dot_segments: Option<bool>,
+    /// Percent-decode the path before matching and the params handed to handlers.
+    /// Encoded slashes never split segments: `%2F` only becomes `/` inside a param.
+    pub percent_decode: Option<bool>,
+    /// Match routes regardless of ASCII case (`/API/Users` reaches `/api/users`); params
+    /// keep the request's casing. Routes can override it with `Route.caseInsensitive`.
+    pub case_insensitive: Option<bool>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TrailingSlash {
+    Strict,
+    Ignore,
+    Redirect,
+}
+
+impl TrailingSlash {
+    pub fn from_config(options: Option<&RoutingOptionsCore>) -> Self {
+        match options.and_then(|o| o.trailing_slash.as_deref()) {
+            Some("ignore") => TrailingSlash::Ignore,
+            Some("redirect") => TrailingSlash::Redirect,
+            _ => TrailingSlash::Strict,
+        }
+    }
+}
+
+/// Apply the enabled normalizations to `path`, in the order: percent-decoding, slash
+/// merging, dot-segment removal. Borrows when nothing changes.
+pub fn normalize_path<'a>(path: &'a str, options: &RoutingOptionsCore) -> Cow<'a, str> {
+    let mut path = Cow::Borrowed(path);
+
+    if options.percent_decode.unwrap_or(false) && path.contains('%') {
+        path = Cow::Owned(decode_segments(&path));
+    }
+    if options.merge_slashes.unwrap_or(false) && path.contains("//") {
+        path = Cow::Owned(merge_slashes(&path));
+    }
+    if options.remove_dot_segments.unwrap_or(false)
+        && path.split('/').any(|s| s == "." || s == "..")
+    {
+        path = Cow::Owned(remove_dot_segments(&path));
+    }
+
+    path
+}
+
+/// Decode percent-escapes except `%2F` and `%25`, so the result has the same segments as
+/// the input and can be decoded once more (per param) without double-decoding. Paths
+/// that would not decode to UTF-8 are returned unchanged.
+pub fn decode_segments(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+
+        match escaped {
+            Some(byte) if byte != b'/' && byte != b'%' => {
+                decoded.push(byte);
+                i += 3;
+            }
+            _ => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded).unwrap_or_else(|_| path.to_string())
+}
+
+pub fn merge_slashes(path: &str) -> String {
+    let mut merged = String::with_capacity(path.len());
+
+    for c in path.chars() {
+        if c == '/' && merged.ends_with('/') {
+            continue;
+        }
+        merged.push(c);
+    }
+
+    merged
+}
+
+/// RFC 3986 dot-segment removal for an absolute path. `..` never climbs above the root.
+pub fn remove_dot_segments(path: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+    let mut trailing_slash = false;
+
+    for segment in path.split('/').skip(1) {
+        trailing_slash = matches!(segment, "." | "..");
+
+        match segment {
+            "." => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+
+    let mut normalized = format!("/{}", segments.join("/"));
+    if trailing_slash && !normalized.ends_with('/') {
+        normalized.push('/');
+    }
+
+    normalized
+}
+
+/// The same path with the trailing slash added or removed; `None` for the root and
+/// for paths starting with `//`, which a `Location` header would send to another host.
+pub fn toggle_trailing_slash(path: &str) -> Option<String> {
+    if path == "/" || path.is_empty() || path.starts_with("//") {
+        return None;
+    }
+
+    Some(match path.strip_suffix('/') {
+        Some(stripped) => stripped.to_string(),
+        None => format!("{path}/"),
+    })
+}
+
+/// Percent-encode what a path segment cannot hold literally, so a path normalized by
+/// `normalize_path` can be sent back in a `Location` header and still name the same
+/// resource. `/` and existing escapes (`%2F`, `%25`) are kept as they are.
+pub fn encode_path(path: &str) -> Cow<'_, str> {
+    let literal = |b: u8| b.is_ascii_alphanumeric() || b"/%-._~!$&'()*+,;=:@".contains(&b);
+
+    if path.bytes().all(literal) {
+        return Cow::Borrowed(path);
+    }
+
+    let mut encoded = String::with_capacity(path.len() + 8);
+    for b in path.bytes() {
+        if literal(b) {
+            encoded.push(b as char);
+        } else {
+            encoded.push_str(&format!("%{b:02X}"));
+        }
+    }
+
+    Cow::Owned(encoded)
+}
+
+/// ASCII-lowercase the literal parts of a matchit path, leaving `{param}` names as they are.
+pub fn fold_case(path: &str) -> String {
+    let mut depth = 0usize;
+
+    path.chars()
+        .map(|c| {
+            match c {
+                '{' => depth += 1,
+                '}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            if depth == 0 { c.to_ascii_lowercase() } else { c }
+        })
+        .collect()
+}
+
+/// Finish decoding params matched against a `decode_segments` path.
+pub fn decode_params(params: &mut AHashMap<String, String>) {
+    for value in params.values_mut() {
+        if value.contains('%')
+            && let Ok(decoded) = urlencoding::decode(value)
+        {
+            *value = decoded.into_owned();
+        }
+    }
+}
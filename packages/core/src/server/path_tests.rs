@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod tests {
+    use super::super::path::*;
+    use ahash::AHashMap;
+
+    fn all_options() -> RoutingOptionsCore {
+        RoutingOptionsCore {
+            trailing_slash: None,
+            merge_slashes: Some(true),
+            remove_dot_segments: Some(true),
+            percent_decode: Some(true),
+            case_insensitive: None,
+        }
+    }
+
+    #[test]
+    fn test_defaults_leave_path_alone() {
+        let options = RoutingOptionsCore::default();
+        for path in ["//users", "/a/../b", "/caf%C3%A9", "/users/"] {
+            assert_eq!(normalize_path(path, &options), path);
+        }
+    }
+
+    #[test]
+    fn test_merge_slashes() {
+        assert_eq!(merge_slashes("//users///1/"), "/users/1/");
+        assert_eq!(merge_slashes("/users"), "/users");
+    }
+
+    #[test]
+    fn test_remove_dot_segments() {
+        assert_eq!(remove_dot_segments("/a/./b/../c"), "/a/c");
+        assert_eq!(remove_dot_segments("/a/b/.."), "/a/");
+        assert_eq!(remove_dot_segments("/../../etc"), "/etc");
+        assert_eq!(remove_dot_segments("/a/."), "/a/");
+        assert_eq!(remove_dot_segments("/a/..b/.c"), "/a/..b/.c");
+    }
+
+    #[test]
+    fn test_decode_segments_keeps_slashes_encoded() {
+        assert_eq!(decode_segments("/caf%C3%A9"), "/café");
+        assert_eq!(decode_segments("/files/a%2Fb"), "/files/a%2Fb");
+        assert_eq!(decode_segments("/100%25"), "/100%25");
+        assert_eq!(decode_segments("/bad%zz%4"), "/bad%zz%4");
+        assert_eq!(decode_segments("/%FF"), "/%FF");
+    }
+
+    #[test]
+    fn test_normalize_order() {
+        let options = all_options();
+        assert_eq!(normalize_path("//a/%2E%2E/b", &options), "/b");
+        assert_eq!(normalize_path("/a/%2e/b", &options), "/a/b");
+    }
+
+    #[test]
+    fn test_decode_params() {
+        let mut params = AHashMap::new();
+        params.insert("file".to_string(), "a%2Fb%25c".to_string());
+        params.insert("plain".to_string(), "x".to_string());
+
+        decode_params(&mut params);
+        assert_eq!(params["file"], "a/b%c");
+        assert_eq!(params["plain"], "x");
+    }
+
+    #[test]
+    fn test_fold_case_keeps_param_names() {
+        assert_eq!(
+            fold_case("/API/Users/{userId}/Files/{*Rest}"),
+            "/api/users/{userId}/files/{*Rest}"
+        );
+        assert_eq!(fold_case("/Static"), "/static");
+    }
+
+    #[test]
+    fn test_toggle_trailing_slash() {
+        assert_eq!(toggle_trailing_slash("/users").as_deref(), Some("/users/"));
+        assert_eq!(toggle_trailing_slash("/users/").as_deref(), Some("/users"));
+        assert_eq!(toggle_trailing_slash("/"), None);
+        // Without `mergeSlashes`, `//evil.example/` would redirect off-site.
+        assert_eq!(toggle_trailing_slash("//evil.example/"), None);
+        assert_eq!(toggle_trailing_slash("//evil.example"), None);
+    }
+
+    #[test]
+    fn test_encode_path_round_trips_decoded_paths() {
+        assert_eq!(encode_path("/users/1"), "/users/1");
+        assert_eq!(encode_path("/a%2Fb"), "/a%2Fb");
+        assert_eq!(encode_path("/café"), "/caf%C3%A9");
+        assert_eq!(encode_path("/a?b#c d"), "/a%3Fb%23c%20d");
+
+        let options = all_options();
+        for raw in ["/caf%C3%A9/", "/a%2Fb/", "/what%3F/", "/100%25"] {
+            assert_eq!(encode_path(&normalize_path(raw, &options)), raw);
+        }
+    }
+
+    #[test]
+    fn test_trailing_slash_policy() {
+        let options = |policy: &str| RoutingOptionsCore {
+            trailing_slash: Some(policy.to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(TrailingSlash::from_config(None), TrailingSlash::Strict);
+        assert_eq!(TrailingSlash::from_config(Some(&options("ignore"))), TrailingSlash::Ignore);
+        assert_eq!(TrailingSlash::from_config(Some(&options("redirect"))), TrailingSlash::Redirect);
+        assert_eq!(TrailingSlash::from_config(Some(&options("bogus"))), TrailingSlash::Strict);
+    }
+}
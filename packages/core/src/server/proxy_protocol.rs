@@ -0,0 +1,122 @@
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// First 12 bytes of every PROXY protocol v2 header.
+pub const V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+
+/// Longest possible v1 header, CRLF included.
+const V1_MAX_LEN: usize = 107;
+
+/// Read the PROXY protocol (v1 or v2) header a load balancer sends ahead of the
+/// connection's own bytes, consuming exactly the header and nothing after it.
+///
+/// Returns the original client address, or `None` when the balancer reports the
+/// connection as `LOCAL`/`UNKNOWN` (e.g. its own health checks).
+pub async fn read_proxy_header<S>(stream: &mut S) -> io::Result<Option<SocketAddr>>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut prefix = [0u8; 12];
+    stream.read_exact(&mut prefix).await?;
+
+    if prefix == V2_SIGNATURE {
+        let mut head = [0u8; 4];
+        stream.read_exact(&mut head).await?;
+
+        let mut payload = vec![0u8; u16::from_be_bytes([head[2], head[3]]) as usize];
+        stream.read_exact(&mut payload).await?;
+
+        return parse_v2(head[0], head[1], &payload);
+    }
+
+    if !prefix.starts_with(b"PROXY ") {
+        return Err(invalid("missing PROXY protocol header"));
+    }
+
+    // v1 has no length prefix, so read byte by byte to stop right at the CRLF.
+    let mut line = prefix.to_vec();
+    while !line.ends_with(b"\r\n") {
+        if line.len() >= V1_MAX_LEN {
+            return Err(invalid("PROXY v1 header too long"));
+        }
+        line.push(stream.read_u8().await?);
+    }
+
+    parse_v1(&line)
+}
+
+/// Parse a v1 header line such as `PROXY TCP4 203.0.113.7 10.0.0.1 51234 443\r\n`.
+pub fn parse_v1(line: &[u8]) -> io::Result<Option<SocketAddr>> {
+    let line = std::str::from_utf8(line)
+        .ok()
+        .and_then(|l| l.strip_suffix("\r\n"))
+        .ok_or_else(|| invalid("malformed PROXY v1 header"))?;
+
+    let mut parts = line.split(' ');
+    if parts.next() != Some("PROXY") {
+        return Err(invalid("malformed PROXY v1 header"));
+    }
+
+    let proto = parts.next();
+    if proto == Some("UNKNOWN") {
+        return Ok(None);
+    }
+
+    let fields: Vec<&str> = parts.collect();
+    let (Some(proto @ ("TCP4" | "TCP6")), [src_ip, dst_ip, src_port, dst_port]) =
+        (proto, fields.as_slice())
+    else {
+        return Err(invalid("malformed PROXY v1 header"));
+    };
+
+    let ip: IpAddr = src_ip.parse().map_err(|_| invalid("invalid PROXY v1 source address"))?;
+    let port: u16 = src_port.parse().map_err(|_| invalid("invalid PROXY v1 source port"))?;
+
+    let valid_dst = dst_ip.parse::<IpAddr>().is_ok() && dst_port.parse::<u16>().is_ok();
+    if !valid_dst || ip.is_ipv4() != (proto == "TCP4") {
+        return Err(invalid("malformed PROXY v1 header"));
+    }
+
+    Ok(Some(SocketAddr::new(ip, port)))
+}
+
+/// Parse the part of a v2 header after the signature: the version/command byte,
+/// the family/protocol byte and the address payload (TLVs are ignored).
+pub fn parse_v2(ver_cmd: u8, family: u8, payload: &[u8]) -> io::Result<Option<SocketAddr>> {
+    if ver_cmd >> 4 != 2 {
+        return Err(invalid("unsupported PROXY protocol version"));
+    }
+
+    match ver_cmd & 0x0F {
+        0x0 => return Ok(None),
+        0x1 => {}
+        _ => return Err(invalid("unsupported PROXY v2 command")),
+    }
+
+    match family >> 4 {
+        // AF_INET: src(4) dst(4) src_port(2) dst_port(2)
+        0x1 => {
+            let addr = payload.get(..12).ok_or_else(|| invalid("truncated PROXY v2 header"))?;
+            let ip = Ipv4Addr::new(addr[0], addr[1], addr[2], addr[3]);
+            let port = u16::from_be_bytes([addr[8], addr[9]]);
+            Ok(Some(SocketAddr::new(ip.into(), port)))
+        }
+        // AF_INET6: src(16) dst(16) src_port(2) dst_port(2)
+        0x2 => {
+            let addr = payload.get(..36).ok_or_else(|| invalid("truncated PROXY v2 header"))?;
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&addr[..16]);
+            let port = u16::from_be_bytes([addr[32], addr[33]]);
+            Ok(Some(SocketAddr::new(Ipv6Addr::from(octets).into(), port)))
+        }
+        // AF_UNSPEC and AF_UNIX carry no IP address to report.
+        _ => Ok(None),
+    }
+}
+
+fn invalid(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod tests {
+    use super::super::proxy_protocol::*;
+    use std::net::SocketAddr;
+    use tokio::io::AsyncReadExt;
+
+    fn v2_header(ver_cmd: u8, family: u8, payload: &[u8]) -> Vec<u8> {
+        let mut header = V2_SIGNATURE.to_vec();
+        header.extend_from_slice(&[ver_cmd, family]);
+        header.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+        header.extend_from_slice(payload);
+        header
+    }
+
+    #[test]
+    fn test_v1_tcp4() {
+        let addr = parse_v1(b"PROXY TCP4 203.0.113.7 10.0.0.1 51234 443\r\n").unwrap();
+        assert_eq!(addr, Some("203.0.113.7:51234".parse::<SocketAddr>().unwrap()));
+    }
+
+    #[test]
+    fn test_v1_tcp6() {
+        let addr = parse_v1(b"PROXY TCP6 2001:db8::1 2001:db8::2 4000 80\r\n").unwrap();
+        assert_eq!(addr, Some("[2001:db8::1]:4000".parse::<SocketAddr>().unwrap()));
+    }
+
+    #[test]
+    fn test_v1_unknown() {
+        assert_eq!(parse_v1(b"PROXY UNKNOWN\r\n").unwrap(), None);
+        assert_eq!(parse_v1(b"PROXY UNKNOWN ffff::1 ffff::2 1 2\r\n").unwrap(), None);
+    }
+
+    #[test]
+    fn test_v1_rejects_malformed() {
+        assert!(parse_v1(b"PROXY TCP4 203.0.113.7 10.0.0.1 51234\r\n").is_err());
+        assert!(parse_v1(b"PROXY TCP4 2001:db8::1 10.0.0.1 1 2\r\n").is_err());
+        assert!(parse_v1(b"PROXY UDP4 1.2.3.4 5.6.7.8 1 2\r\n").is_err());
+        assert!(parse_v1(b"PROXY TCP4 1.2.3.4 5.6.7.8 99999 2\r\n").is_err());
+        assert!(parse_v1(b"PROXY TCP4 1.2.3.4 5.6.7.8 1 2\n").is_err());
+    }
+
+    #[test]
+    fn test_v2_inet() {
+        let payload = [203, 0, 113, 7, 10, 0, 0, 1, 0xC8, 0x22, 0x01, 0xBB];
+        let addr = parse_v2(0x21, 0x11, &payload).unwrap();
+        assert_eq!(addr, Some("203.0.113.7:51234".parse::<SocketAddr>().unwrap()));
+    }
+
+    #[test]
+    fn test_v2_inet6() {
+        let mut payload = vec![0u8; 36];
+        payload[0] = 0x20;
+        payload[1] = 0x01;
+        payload[15] = 0x01;
+        payload[32..34].copy_from_slice(&4000u16.to_be_bytes());
+
+        let addr = parse_v2(0x21, 0x21, &payload).unwrap();
+        assert_eq!(addr, Some("[2001::1]:4000".parse::<SocketAddr>().unwrap()));
+    }
+
+    #[test]
+    fn test_v2_local_and_unspec() {
+        assert_eq!(parse_v2(0x20, 0x00, &[]).unwrap(), None);
+        assert_eq!(parse_v2(0x21, 0x00, &[]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_v2_rejects_bad_version_and_truncation() {
+        assert!(parse_v2(0x11, 0x11, &[0; 12]).is_err());
+        assert!(parse_v2(0x21, 0x11, &[0; 8]).is_err());
+        assert!(parse_v2(0x21, 0x21, &[0; 12]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_consumes_only_the_header() {
+        let mut input: &[u8] = b"PROXY TCP4 203.0.113.7 10.0.0.1 51234 443\r\nGET / HTTP/1.1\r\n";
+        let addr = read_proxy_header(&mut input).await.unwrap();
+        assert_eq!(addr, Some("203.0.113.7:51234".parse::<SocketAddr>().unwrap()));
+
+        let mut rest = String::new();
+        input.read_to_string(&mut rest).await.unwrap();
+        assert_eq!(rest, "GET / HTTP/1.1\r\n");
+    }
+
+    #[tokio::test]
+    async fn test_read_v2_with_tlvs() {
+        let payload = [203, 0, 113, 7, 10, 0, 0, 1, 0xC8, 0x22, 0x01, 0xBB, 0x04, 0x00, 0x01, 0x00];
+        let mut bytes = v2_header(0x21, 0x11, &payload);
+        bytes.extend_from_slice(b"GET");
+
+        let mut input = bytes.as_slice();
+        let addr = read_proxy_header(&mut input).await.unwrap();
+        assert_eq!(addr, Some("203.0.113.7:51234".parse::<SocketAddr>().unwrap()));
+        assert_eq!(input, b"GET");
+    }
+
+    #[tokio::test]
+    async fn test_read_rejects_missing_header() {
+        let mut input: &[u8] = b"GET / HTTP/1.1\r\nHost: x\r\n\r\n";
+        assert!(read_proxy_header(&mut input).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_rejects_overlong_v1() {
+        let mut line = b"PROXY TCP4 ".to_vec();
+        line.extend(std::iter::repeat_n(b'1', 200));
+        let mut input = line.as_slice();
+        assert!(read_proxy_header(&mut input).await.is_err());
+    }
+}
@@ -1,12 +1,32 @@
 use ahash::AHashMap;
-use matchit::{Match, Router as MatchitRouter};
-use parking_lot::RwLock;
-use std::sync::Arc;
+use arc_swap::ArcSwap;
+use matchit::{Match, Params, Router as MatchitRouter};
+use parking_lot::Mutex;
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
 
-use super::routes::CompiledRoute;
+use super::{
+    host::HostPattern,
+    params::{PathParam, same_constraints, satisfies},
+    path::{TrailingSlash, fold_case, toggle_trailing_slash},
+    routes::CompiledRoute,
+};
 
+/// The routes of one shape, constrained ones first.
+type Candidates = Arc<[Arc<CompiledRoute>]>;
+
+#[derive(Clone)]
 pub struct TrieRouter {
-    inner: MatchitRouter<Arc<CompiledRoute>>,
+    /// Routes keyed by shape (see `RoutePattern`). Routes of the same shape are tried in
+    /// turn, constrained ones first; the first whose constraints accept the request wins.
+    inner: MatchitRouter<Candidates>,
+    /// Each shape in a trie of its own, to find the other shapes a request matches when
+    /// every route of the node matchit picked turned it down.
+    shapes: AHashMap<Box<str>, MatchitRouter<Candidates>>,
+    /// The same routes keyed by shape, since matchit cannot list or update its routes.
+    routes: AHashMap<Box<str>, Vec<Arc<CompiledRoute>>>,
 }
 
 impl Default for TrieRouter {
@@ -17,21 +37,161 @@ impl Default for TrieRouter {
 
 impl TrieRouter {
     pub fn new() -> Self {
-        Self { inner: MatchitRouter::new() }
+        Self { inner: MatchitRouter::new(), shapes: AHashMap::new(), routes: AHashMap::new() }
+    }
+
+    /// Fails when the path conflicts with another shape, or a route of the same shape
+    /// accepts exactly the same requests.
+    pub fn insert(&mut self, route: Arc<CompiledRoute>) -> Result<(), String> {
+        let Some(candidates) = self.routes.get(&route.shape) else {
+            self.inner
+                .insert(&*route.shape, Arc::from([route.clone()]))
+                .map_err(|e| format!("Failed to insert route: {e}"))?;
+            let shape = route.shape.clone();
+            self.publish(&shape, vec![route]);
+            return Ok(());
+        };
+
+        if let Some(existing) =
+            candidates.iter().find(|c| same_constraints(&c.params, &route.params))
+        {
+            return Err(format!(
+                "Failed to insert route: {} conflicts with previously registered route {}",
+                route.original_path, existing.original_path
+            ));
+        }
+
+        let mut candidates = candidates.clone();
+        candidates.push(route.clone());
+        candidates.sort_by_key(|c| c.params.iter().all(|p| p.constraint.is_none()));
+        self.publish(&route.shape, candidates);
+
+        Ok(())
+    }
+
+    /// Match `lookup_path`, taking param values from the same byte ranges of `original`.
+    /// When the constraints of every route of the best matching shape turn the request
+    /// down, the other shapes it matches are tried in turn, most specific first.
+    pub fn find(&self, lookup_path: &str, original: &str) -> Option<RouteMatch> {
+        let matched = self.inner.at(lookup_path).ok()?;
+        if let Some(found) = accept(&matched, lookup_path, original) {
+            return Some(found);
+        }
+
+        let tried = &matched.value.first()?.shape;
+        let mut fallback = MatchitRouter::new();
+        for (shape, trie) in &self.shapes {
+            if shape != tried
+                && let Ok(other) = trie.at(lookup_path)
+            {
+                // These shapes already share `inner`, so they cannot conflict.
+                let _ = fallback.insert(&**shape, other.value.clone());
+            }
+        }
+
+        loop {
+            let matched = fallback.at(lookup_path).ok()?;
+            if let Some(found) = accept(&matched, lookup_path, original) {
+                return Some(found);
+            }
+
+            let tried = matched.value.first()?.shape.clone();
+            fallback.remove(&*tried);
+        }
+    }
+
+    /// Remove the route of `shape` whose params have the same constraints as `params`.
+    pub fn remove(&mut self, shape: &str, params: &[PathParam]) -> Option<Arc<CompiledRoute>> {
+        let candidates = self.routes.get(shape)?;
+        let index = candidates.iter().position(|c| same_constraints(&c.params, params))?;
+
+        let mut candidates = candidates.clone();
+        let removed = candidates.remove(index);
+
+        if candidates.is_empty() {
+            self.routes.remove(shape);
+            self.inner.remove(shape);
+            self.shapes.remove(shape);
+        } else {
+            self.publish(shape, candidates);
+        }
+
+        Some(removed)
+    }
+
+    pub fn get(&self, shape: &str, params: &[PathParam]) -> Option<&Arc<CompiledRoute>> {
+        self.routes.get(shape)?.iter().find(|c| same_constraints(&c.params, params))
+    }
+
+    /// Replace the routes of an existing shape, in `inner` and in its own trie.
+    fn publish(&mut self, shape: &str, candidates: Vec<Arc<CompiledRoute>>) {
+        let published: Candidates = Arc::from(candidates.as_slice());
+
+        // The shape was inserted before, so it cannot conflict now.
+        self.inner.remove(shape);
+        let _ = self.inner.insert(shape, published.clone());
+
+        let mut trie = MatchitRouter::new();
+        let _ = trie.insert(shape, published);
+        self.shapes.insert(shape.into(), trie);
+
+        self.routes.insert(shape.into(), candidates);
+    }
+
+    pub fn routes(&self) -> impl Iterator<Item = &Arc<CompiledRoute>> {
+        self.routes.values().flatten()
+    }
+
+    pub fn len(&self) -> usize {
+        self.routes.values().map(Vec::len).sum()
     }
 
-    pub fn insert(&mut self, path: &str, route: Arc<CompiledRoute>) -> Result<(), String> {
-        self.inner.insert(path, route).map_err(|e| format!("Failed to insert route: {e}"))
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
     }
+}
+
+/// The first route of `matched` whose constraints accept the request.
+fn accept(
+    matched: &Match<'_, '_, &Candidates>,
+    lookup_path: &str,
+    original: &str,
+) -> Option<RouteMatch> {
+    let values = positional_values(&matched.params, lookup_path, original);
+    let route = matched.value.iter().find(|route| satisfies(&route.params, &values))?;
+    Some(RouteMatch::new(route.clone(), &values))
+}
 
-    pub fn find<'a>(&'a self, path: &'a str) -> Option<Match<'a, 'a, &'a Arc<CompiledRoute>>> {
-        self.inner.at(path).ok()
+/// Param values of a match by position, taken from the same byte ranges of `original`.
+/// ASCII case folding keeps byte offsets, so the ranges line up.
+fn positional_values<'a>(
+    params: &Params<'_, 'a>,
+    lookup_path: &'a str,
+    original: &'a str,
+) -> Vec<&'a str> {
+    let base = lookup_path.as_ptr() as usize;
+    let mut values = vec![""; params.len()];
+
+    for (key, value) in params.iter() {
+        let value = (value.as_ptr() as usize)
+            .checked_sub(base)
+            .and_then(|start| original.get(start..start + value.len()))
+            .unwrap_or(value);
+
+        if let Some(slot) = key.parse::<usize>().ok().and_then(|index| values.get_mut(index)) {
+            *slot = value;
+        }
     }
+
+    values
 }
 
+#[derive(Clone)]
 pub struct HttpRouter {
     static_routes: AHashMap<Box<str>, Arc<CompiledRoute>>,
     dynamic_routes: TrieRouter,
+    /// Number of case-insensitive routes; lookups only fold case when there are any.
+    case_insensitive: usize,
 }
 
 impl Default for HttpRouter {
@@ -42,40 +202,110 @@ impl Default for HttpRouter {
 
 impl HttpRouter {
     pub fn new() -> Self {
-        Self { static_routes: AHashMap::new(), dynamic_routes: TrieRouter::new() }
+        Self {
+            static_routes: AHashMap::new(),
+            dynamic_routes: TrieRouter::new(),
+            case_insensitive: 0,
+        }
     }
 
+    /// Case-insensitive routes are expected with their path already folded by `fold_case`.
+    /// A static path registered again replaces the earlier route, but `/API`
+    /// (case-insensitive) and `/api` only share a folded path, so they conflict.
     pub fn insert(&mut self, route: CompiledRoute) -> Result<(), String> {
         let route_arc = Arc::new(route);
 
         if Self::is_static_route(&route_arc.path) {
-            self.static_routes.insert(route_arc.path.clone(), route_arc.clone());
+            if let Some(existing) = self.static_routes.get(&route_arc.path)
+                && existing.original_path != route_arc.original_path
+            {
+                return Err(format!(
+                    "Failed to insert route: {} conflicts with previously registered route {}",
+                    route_arc.original_path, existing.original_path
+                ));
+            }
+
+            let replaced = self.static_routes.insert(route_arc.path.clone(), route_arc.clone());
+            if replaced.is_some_and(|route| route.case_insensitive) {
+                self.case_insensitive -= 1;
+            }
         } else {
-            self.dynamic_routes.insert(&route_arc.path, route_arc.clone())?;
+            self.dynamic_routes.insert(route_arc.clone())?;
+        }
+
+        if route_arc.case_insensitive {
+            self.case_insensitive += 1;
         }
 
         Ok(())
     }
 
-    pub fn find<'a>(&'a self, path: &'a str) -> Option<RouteMatch> {
-        if let Some(route) = self.static_routes.get(path) {
-            return Some(RouteMatch { route: route.clone(), params: AHashMap::new() });
+    /// Match `path` exactly, then, if that only reaches the catch-all, against the
+    /// case-insensitive routes. Params keep the casing of the request either way.
+    pub fn find(&self, path: &str) -> Option<RouteMatch> {
+        let exact = self.find_as(path, path);
+
+        let settled = exact.as_ref().is_some_and(|m| !m.is_catch_all());
+        if settled || self.case_insensitive == 0 || !path.bytes().any(|b| b.is_ascii_uppercase()) {
+            return exact;
+        }
+
+        let folded = path.to_ascii_lowercase();
+        self.find_as(&folded, path)
+            .filter(|m| m.route.case_insensitive && !m.is_catch_all())
+            .or(exact)
+    }
+
+    /// Match `lookup_path`, taking param values from the same byte ranges of `original`.
+    fn find_as(&self, lookup_path: &str, original: &str) -> Option<RouteMatch> {
+        if let Some(route) = self.static_routes.get(lookup_path) {
+            return Some(RouteMatch::new(route.clone(), &[]));
         }
 
-        if let Some(matched) = self.dynamic_routes.find(path) {
-            let mut params = AHashMap::new();
+        self.dynamic_routes.find(lookup_path, original)
+    }
+
+    /// Remove the route of `shape` with the constraints of `params`, or the
+    /// case-insensitive route whose folded shape it is.
+    pub fn remove(&mut self, shape: &str, params: &[PathParam]) -> Option<Arc<CompiledRoute>> {
+        let removed = self.remove_exact(shape, params).or_else(|| {
+            let folded = fold_case(shape);
+            let route = self.get(&folded, params)?;
 
-            for (key, value) in matched.params.iter() {
-                params.insert(key.to_string(), value.to_string());
+            if folded == shape || !route.case_insensitive {
+                return None;
             }
+            self.remove_exact(&folded, params)
+        });
+
+        if removed.as_ref().is_some_and(|route| route.case_insensitive) {
+            self.case_insensitive -= 1;
+        }
+
+        removed
+    }
 
-            return Some(RouteMatch { route: matched.value.clone(), params });
+    fn get(&self, shape: &str, params: &[PathParam]) -> Option<&Arc<CompiledRoute>> {
+        if Self::is_static_route(shape) {
+            self.static_routes.get(shape)
+        } else {
+            self.dynamic_routes.get(shape, params)
+        }
+    }
+
+    fn remove_exact(&mut self, shape: &str, params: &[PathParam]) -> Option<Arc<CompiledRoute>> {
+        if Self::is_static_route(shape) {
+            self.static_routes.remove(shape)
+        } else {
+            self.dynamic_routes.remove(shape, params)
         }
+    }
 
-        None
+    pub fn routes(&self) -> impl Iterator<Item = &Arc<CompiledRoute>> {
+        self.static_routes.values().chain(self.dynamic_routes.routes())
     }
 
-    fn is_static_route(path: &str) -> bool {
+    pub fn is_static_route(path: &str) -> bool {
         !path.contains(':') && !path.contains('*') && !path.contains('{')
     }
 }
@@ -85,8 +315,296 @@ pub struct RouteMatch {
     pub params: AHashMap<String, String>,
 }
 
+impl RouteMatch {
+    /// Name the positional `values` after the route's params.
+    fn new(route: Arc<CompiledRoute>, values: &[&str]) -> Self {
+        let params = route
+            .params
+            .iter()
+            .zip(values)
+            .map(|(p, v)| (p.name.to_string(), v.to_string()))
+            .collect();
+
+        Self { route, params }
+    }
+
+    /// Add the params captured from the host. Path params of the same name win.
+    fn with_host_params(mut self, host_params: Vec<(String, String)>) -> Self {
+        for (name, value) in host_params {
+            self.params.entry(name).or_insert(value);
+        }
+        self
+    }
+
+    /// Matched through a root wildcard such as `/{*path}`, e.g. the catch-all route of
+    /// global middlewares, rather than a route written for this path.
+    pub fn is_catch_all(&self) -> bool {
+        self.route.shape.starts_with("/{*")
+    }
+}
+
+/// Outcome of resolving a request against every method's routes.
+pub enum RouteLookup {
+    Found(RouteMatch),
+    /// The path is routed, but not for this method. Holds the methods it is routed for.
+    MethodNotAllowed(Vec<Box<str>>),
+    NotFound,
+    /// Only the other trailing-slash spelling of the path is routed. Holds that path.
+    Redirect(String),
+}
+
+/// Routes of one host pattern (or of no host), keyed by HTTP method.
+#[derive(Clone, Default)]
+pub struct HostRoutes {
+    routers: AHashMap<Box<str>, HttpRouter>,
+}
+
+impl HostRoutes {
+    pub fn insert(&mut self, method: &str, route: CompiledRoute) -> Result<(), String> {
+        let Some(router) = self.routers.get_mut(method) else {
+            let mut router = HttpRouter::new();
+            router.insert(route)?;
+            self.routers.insert(method.into(), router);
+            return Ok(());
+        };
+
+        router.insert(route)
+    }
+
+    /// Remove the route registered for `method` with the given shape and constraints.
+    pub fn remove(
+        &mut self,
+        method: &str,
+        shape: &str,
+        params: &[PathParam],
+    ) -> Option<Arc<CompiledRoute>> {
+        self.routers.get_mut(method)?.remove(shape, params)
+    }
+
+    pub fn find(&self, method: &str, path: &str) -> Option<RouteMatch> {
+        self.routers.get(method)?.find(path)
+    }
+
+    /// Like `find`, but tells an unknown path apart from a path routed for other methods.
+    /// The catch-all route only serves paths no other method routes.
+    pub fn lookup(&self, method: &str, path: &str) -> RouteLookup {
+        // HEAD falls back to the GET route; the handler drops the body.
+        let fallback = (method == "HEAD").then_some("GET");
+        let mut catch_all = None;
+
+        for method in std::iter::once(method).chain(fallback) {
+            match self.find(method, path) {
+                Some(matched) if matched.is_catch_all() => catch_all = catch_all.or(Some(matched)),
+                Some(matched) => return RouteLookup::Found(matched),
+                None => {}
+            }
+        }
+
+        let allowed = self.allowed_methods(path);
+        if !allowed.is_empty() {
+            return RouteLookup::MethodNotAllowed(allowed);
+        }
+
+        catch_all.map_or(RouteLookup::NotFound, RouteLookup::Found)
+    }
+
+    /// `lookup` under a trailing-slash policy. When `path` is unrouted, or only caught
+    /// by the catch-all, a route for the other spelling (`/users` vs `/users/`) is served
+    /// (`Ignore`) or redirected to (`Redirect`). When the other spelling is routed for
+    /// other methods only, the request is not allowed rather than redirected.
+    pub fn resolve(&self, method: &str, path: &str, trailing_slash: TrailingSlash) -> RouteLookup {
+        let lookup = self.lookup(method, path);
+
+        let fallback = match &lookup {
+            RouteLookup::NotFound => true,
+            RouteLookup::Found(matched) => matched.is_catch_all(),
+            _ => false,
+        };
+        if trailing_slash == TrailingSlash::Strict || !fallback {
+            return lookup;
+        }
+
+        let Some(other) = toggle_trailing_slash(path) else {
+            return lookup;
+        };
+
+        match self.lookup(method, &other) {
+            RouteLookup::NotFound => lookup,
+            RouteLookup::Found(matched) if matched.is_catch_all() => lookup,
+            RouteLookup::Found(_) if trailing_slash == TrailingSlash::Redirect => {
+                RouteLookup::Redirect(other)
+            }
+            alternative => alternative,
+        }
+    }
+
+    /// Methods `path` answers to, sorted: the routed ones plus the automatic HEAD and
+    /// OPTIONS. Empty when the path is not routed at all, or only by the catch-all.
+    pub fn allowed_methods(&self, path: &str) -> Vec<Box<str>> {
+        allowed_methods(&self.routers, path)
+    }
+
+    pub fn routes(&self) -> Vec<Arc<CompiledRoute>> {
+        self.routers.values().flat_map(|router| router.routes()).cloned().collect()
+    }
+
+    pub fn route_count(&self) -> usize {
+        self.routers.values().map(|r| r.static_routes.len() + r.dynamic_routes.len()).sum()
+    }
+}
+
+/// One immutable generation of a server's routes. Requests are resolved against the
+/// routes of each host pattern matching their host, most specific first, and then
+/// against the routes bound to no host.
+#[derive(Clone, Default)]
+pub struct RouteTable {
+    default: HostRoutes,
+    /// Ordered by `HostPattern::specificity`.
+    hosts: Vec<(HostPattern, HostRoutes)>,
+}
+
+impl RouteTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, method: &str, route: CompiledRoute) -> Result<(), String> {
+        let Some(pattern) = route.host.clone() else {
+            return self.default.insert(method, route);
+        };
+
+        if let Some((_, routes)) =
+            self.hosts.iter_mut().find(|(p, _)| p.as_str() == pattern.as_str())
+        {
+            return routes.insert(method, route);
+        }
+
+        let mut routes = HostRoutes::default();
+        routes.insert(method, route)?;
+
+        let index = self.hosts.partition_point(|(p, _)| p.specificity() <= pattern.specificity());
+        self.hosts.insert(index, (pattern, routes));
+        Ok(())
+    }
+
+    /// Remove the route registered for `method` and `host` (a `HostPattern` source)
+    /// with the given shape and constraints.
+    pub fn remove(
+        &mut self,
+        host: Option<&str>,
+        method: &str,
+        shape: &str,
+        params: &[PathParam],
+    ) -> Option<Arc<CompiledRoute>> {
+        let Some(host) = host else {
+            return self.default.remove(method, shape, params);
+        };
+
+        let index = self.hosts.iter().position(|(p, _)| p.as_str() == host)?;
+        let removed = self.hosts[index].1.remove(method, shape, params);
+
+        if self.hosts[index].1.route_count() == 0 {
+            self.hosts.remove(index);
+        }
+
+        removed
+    }
+
+    /// The route tables serving `host`, with the params captured from it.
+    fn layers<'a>(
+        &'a self,
+        host: Option<&'a str>,
+    ) -> impl Iterator<Item = (&'a HostRoutes, Vec<(String, String)>)> + 'a {
+        self.hosts
+            .iter()
+            .filter_map(move |(pattern, routes)| Some((routes, pattern.matches(host?)?)))
+            .chain(std::iter::once((&self.default, Vec::new())))
+    }
+
+    pub fn find(&self, host: Option<&str>, method: &str, path: &str) -> Option<RouteMatch> {
+        self.layers(host).find_map(|(routes, host_params)| {
+            routes.find(method, path).map(|matched| matched.with_host_params(host_params))
+        })
+    }
+
+    pub fn lookup(&self, host: Option<&str>, method: &str, path: &str) -> RouteLookup {
+        self.resolve(host, method, path, TrailingSlash::Strict)
+    }
+
+    /// `HostRoutes::resolve` on each table serving `host` until one finds a route or
+    /// redirects. Otherwise the path is allowed the methods of every table, and only
+    /// falls to a catch-all route when no table routes it at all.
+    pub fn resolve(
+        &self,
+        host: Option<&str>,
+        method: &str,
+        path: &str,
+        trailing_slash: TrailingSlash,
+    ) -> RouteLookup {
+        let mut allowed = Vec::new();
+        let mut catch_all = None;
+
+        for (routes, host_params) in self.layers(host) {
+            match routes.resolve(method, path, trailing_slash) {
+                RouteLookup::Found(matched) if matched.is_catch_all() => {
+                    catch_all = catch_all.or(Some(matched.with_host_params(host_params)));
+                }
+                RouteLookup::Found(matched) => {
+                    return RouteLookup::Found(matched.with_host_params(host_params));
+                }
+                RouteLookup::MethodNotAllowed(methods) => allowed.extend(methods),
+                RouteLookup::NotFound => {}
+                redirect => return redirect,
+            }
+        }
+
+        if allowed.is_empty() {
+            return catch_all.map_or(RouteLookup::NotFound, RouteLookup::Found);
+        }
+
+        allowed.sort();
+        allowed.dedup();
+        RouteLookup::MethodNotAllowed(allowed)
+    }
+
+    pub fn allowed_methods(&self, host: Option<&str>, path: &str) -> Vec<Box<str>> {
+        let mut allowed: Vec<Box<str>> =
+            self.layers(host).flat_map(|(routes, _)| routes.allowed_methods(path)).collect();
+
+        allowed.sort();
+        allowed.dedup();
+        allowed
+    }
+
+    /// Every route, ordered by path, method and then host.
+    pub fn routes(&self) -> Vec<Arc<CompiledRoute>> {
+        let mut routes: Vec<_> = self.default.routes();
+        routes.extend(self.hosts.iter().flat_map(|(_, routes)| routes.routes()));
+
+        routes.sort_by(|a, b| {
+            let host = |route: &CompiledRoute| route.host.as_ref().map(|h| h.as_str().to_string());
+            (&a.path, &a.method, host(a)).cmp(&(&b.path, &b.method, host(b)))
+        });
+        routes
+    }
+
+    pub fn route_count(&self) -> usize {
+        self.default.route_count()
+            + self.hosts.iter().map(|(_, routes)| routes.route_count()).sum::<usize>()
+    }
+}
+
+/// Route tables of one `ServerCore`. Requests read the current `RouteTable` without
+/// locking; changes are made on a copy and published atomically, so requests never see
+/// a half-applied update and in-flight requests keep the route they matched.
+///
+/// Until the table is first read (at the latest when the server starts), nothing can
+/// observe it, so routes are added to it in place rather than to a copy each time.
 pub struct GlobalRouter {
-    routers: RwLock<AHashMap<Box<str>, HttpRouter>>,
+    table: ArcSwap<RouteTable>,
+    /// Held while changing routes. Holds the table being built until it is published.
+    writer: Mutex<Option<RouteTable>>,
+    staged: AtomicBool,
 }
 
 impl Default for GlobalRouter {
@@ -97,28 +615,146 @@ impl Default for GlobalRouter {
 
 impl GlobalRouter {
     pub fn new() -> Self {
-        Self { routers: RwLock::new(AHashMap::new()) }
+        Self {
+            table: ArcSwap::from_pointee(RouteTable::new()),
+            writer: Mutex::new(Some(RouteTable::new())),
+            staged: AtomicBool::new(true),
+        }
     }
 
     pub fn insert(&self, method: &str, route: CompiledRoute) -> Result<(), String> {
-        let mut routers = self.routers.write();
-        let router = routers.entry(method.to_string().into_boxed_str()).or_default();
+        let mut writer = self.writer.lock();
 
-        router.insert(route)
+        // A failed insert leaves the table as it was, so the staged table needs no copy.
+        if let Some(table) = writer.as_mut() {
+            return table.insert(method, route);
+        }
+        self.apply(&mut writer, |table| table.insert(method, route))
     }
 
-    pub fn find(&self, method: &str, path: &str) -> Option<RouteMatch> {
-        let routers = self.routers.read();
+    pub fn remove(
+        &self,
+        host: Option<&str>,
+        method: &str,
+        shape: &str,
+        params: &[PathParam],
+    ) -> bool {
+        self.update(|table| table.remove(host, method, shape, params).ok_or(())).is_ok()
+    }
+
+    /// Put `route` in place of the one registered for the same host, method, path and
+    /// constraints, or add it. Returns whether a route was replaced.
+    pub fn replace(&self, method: &str, route: CompiledRoute) -> Result<bool, String> {
+        self.update(|table| {
+            let host = route.host.as_ref().map(|h| h.as_str());
+            let replaced = table.remove(host, method, &route.shape, &route.params).is_some();
+            table.insert(method, route)?;
+            Ok(replaced)
+        })
+    }
+
+    /// Publish `table` in place of the current routes.
+    pub fn swap(&self, table: RouteTable) {
+        let mut writer = self.writer.lock();
+        *writer = None;
+        self.table.store(Arc::new(table));
+        self.staged.store(false, Ordering::Release);
+    }
+
+    /// Publish the table built so far. Later changes are made on a copy.
+    pub fn publish(&self) {
+        if !self.staged.load(Ordering::Acquire) {
+            return;
+        }
+
+        let mut writer = self.writer.lock();
+        if let Some(table) = writer.take() {
+            self.table.store(Arc::new(table));
+            self.staged.store(false, Ordering::Release);
+        }
+    }
 
-        if let Some(router) = routers.get(method) {
-            return router.find(path);
+    fn update<T, E>(&self, change: impl FnOnce(&mut RouteTable) -> Result<T, E>) -> Result<T, E> {
+        let mut writer = self.writer.lock();
+        self.apply(&mut writer, change)
+    }
+
+    /// Apply `change` to a copy of the current table and keep it, unless it fails.
+    fn apply<T, E>(
+        &self,
+        staged: &mut Option<RouteTable>,
+        change: impl FnOnce(&mut RouteTable) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let mut table = match staged {
+            Some(table) => table.clone(),
+            None => RouteTable::clone(&self.table.load()),
+        };
+        let result = change(&mut table)?;
+
+        match staged {
+            Some(staged) => *staged = table,
+            None => self.table.store(Arc::new(table)),
         }
 
-        None
+        Ok(result)
+    }
+
+    fn current(&self) -> arc_swap::Guard<Arc<RouteTable>> {
+        self.publish();
+        self.table.load()
+    }
+
+    pub fn find(&self, host: Option<&str>, method: &str, path: &str) -> Option<RouteMatch> {
+        self.current().find(host, method, path)
+    }
+
+    pub fn lookup(&self, host: Option<&str>, method: &str, path: &str) -> RouteLookup {
+        self.current().lookup(host, method, path)
+    }
+
+    pub fn resolve(
+        &self,
+        host: Option<&str>,
+        method: &str,
+        path: &str,
+        trailing_slash: TrailingSlash,
+    ) -> RouteLookup {
+        self.current().resolve(host, method, path, trailing_slash)
+    }
+
+    pub fn allowed_methods(&self, host: Option<&str>, path: &str) -> Vec<Box<str>> {
+        self.current().allowed_methods(host, path)
+    }
+
+    pub fn routes(&self) -> Vec<Arc<CompiledRoute>> {
+        self.current().routes()
     }
 
     pub fn route_count(&self) -> usize {
-        let routers = self.routers.read();
-        routers.values().map(|r| r.static_routes.len()).sum()
+        self.current().route_count()
+    }
+}
+
+fn allowed_methods(routers: &AHashMap<Box<str>, HttpRouter>, path: &str) -> Vec<Box<str>> {
+    let mut allowed: Vec<Box<str>> = routers
+        .iter()
+        .filter(|(_, router)| router.find(path).is_some_and(|m| !m.is_catch_all()))
+        .map(|(method, _)| method.clone())
+        .collect();
+
+    if allowed.is_empty() {
+        return allowed;
+    }
+
+    let routed = |allowed: &[Box<str>], method: &str| allowed.iter().any(|m| &**m == method);
+
+    if routed(&allowed, "GET") && !routed(&allowed, "HEAD") {
+        allowed.push("HEAD".into());
+    }
+    if !routed(&allowed, "OPTIONS") {
+        allowed.push("OPTIONS".into());
     }
+
+    allowed.sort();
+    allowed
 }
@@ -0,0 +1,242 @@
+#[cfg(test)]
+mod tests {
+    use super::super::host::HostPattern;
+    use super::super::params::RoutePattern;
+    use super::super::path::TrailingSlash;
+    use super::super::router::*;
+    use super::super::routes::{CompiledRoute, ResponseStrategy};
+    use http_body_util::Full;
+    use hyper::{Response, body::Bytes};
+
+    fn route(method: &str, path: &str) -> CompiledRoute {
+        let pattern = RoutePattern::parse(path).unwrap();
+        CompiledRoute {
+            method: method.into(),
+            original_path: path.into(),
+            path: pattern.path.into(),
+            shape: pattern.shape.into(),
+            params: pattern.params.into(),
+            segments: Box::new([]),
+            strategy: ResponseStrategy::FullStatic(Response::new(Full::new(Bytes::new()))),
+            schema: None,
+            max_request_size: None,
+            timeout: None,
+            case_insensitive: false,
+            host: None,
+        }
+    }
+
+    /// The routes `kito` registers for global middlewares.
+    const CATCH_ALL_METHODS: [&str; 6] = ["GET", "POST", "PUT", "DELETE", "PATCH", "OPTIONS"];
+
+    fn table(routes: &[(&str, &str)]) -> RouteTable {
+        let mut table = RouteTable::new();
+        for &(method, path) in routes {
+            table.insert(method, route(method, path)).unwrap();
+        }
+        table
+    }
+
+    fn with_catch_all(routes: &[(&str, &str)]) -> RouteTable {
+        let mut table = table(routes);
+        for method in CATCH_ALL_METHODS {
+            table.insert(method, route(method, "/{*path}")).unwrap();
+        }
+        table
+    }
+
+    fn found(lookup: RouteLookup) -> String {
+        match lookup {
+            RouteLookup::Found(matched) => matched.route.original_path.to_string(),
+            _ => panic!("expected a route"),
+        }
+    }
+
+    fn not_allowed(lookup: RouteLookup) -> Vec<Box<str>> {
+        match lookup {
+            RouteLookup::MethodNotAllowed(allowed) => allowed,
+            _ => panic!("expected 405"),
+        }
+    }
+
+    #[test]
+    fn test_catch_all_does_not_hide_method_not_allowed() {
+        let table = with_catch_all(&[("GET", "/users"), ("GET", "/users/:id")]);
+
+        assert_eq!(found(table.lookup(None, "GET", "/users")), "/users");
+        assert_eq!(found(table.lookup(None, "HEAD", "/users/1")), "/users/:id");
+        assert_eq!(
+            not_allowed(table.lookup(None, "POST", "/users")),
+            ["GET".into(), "HEAD".into(), "OPTIONS".into()]
+        );
+
+        // Paths nobody routes still reach the global middlewares.
+        assert_eq!(found(table.lookup(None, "POST", "/unknown")), "/{*path}");
+        assert_eq!(found(table.lookup(None, "HEAD", "/unknown")), "/{*path}");
+    }
+
+    #[test]
+    fn test_allowed_methods_ignore_catch_all() {
+        let table = with_catch_all(&[("GET", "/users"), ("POST", "/users")]);
+
+        assert_eq!(
+            table.allowed_methods(None, "/users"),
+            ["GET".into(), "HEAD".into(), "OPTIONS".into(), "POST".into()]
+        );
+        assert!(table.allowed_methods(None, "/unknown").is_empty());
+    }
+
+    #[test]
+    fn test_automatic_options_behind_catch_all() {
+        let table = with_catch_all(&[("GET", "/users"), ("OPTIONS", "/custom")]);
+
+        // The handler answers OPTIONS itself when the lookup reports the allowed methods.
+        assert_eq!(
+            not_allowed(table.lookup(None, "OPTIONS", "/users")),
+            ["GET".into(), "HEAD".into(), "OPTIONS".into()]
+        );
+        assert_eq!(found(table.lookup(None, "OPTIONS", "/custom")), "/custom");
+        assert_eq!(found(table.lookup(None, "OPTIONS", "/unknown")), "/{*path}");
+    }
+
+    #[test]
+    fn test_catch_all_does_not_hide_host_routes() {
+        let mut table = with_catch_all(&[]);
+        let mut api = route("GET", "/users");
+        api.host = Some(HostPattern::parse("api.example.com").unwrap());
+        table.insert("GET", api).unwrap();
+
+        assert_eq!(
+            not_allowed(table.lookup(Some("api.example.com"), "POST", "/users")),
+            ["GET".into(), "HEAD".into(), "OPTIONS".into()]
+        );
+        assert_eq!(found(table.lookup(Some("www.example.com"), "POST", "/users")), "/{*path}");
+    }
+
+    #[test]
+    fn test_wildcard_fallback_checks_constraints() {
+        let table = table(&[("GET", "/files/:id<int>/{*rest}"), ("GET", "/files/:id<int>")]);
+
+        let matched = table.find(None, "GET", "/files/12/a/b").unwrap();
+        assert_eq!(matched.params["id"], "12");
+        assert_eq!(matched.params["rest"], "a/b");
+
+        assert!(table.find(None, "GET", "/files/abc/x").is_none());
+        assert!(table.find(None, "GET", "/files/abc").is_none());
+        assert!(matches!(table.lookup(None, "GET", "/files/abc/x"), RouteLookup::NotFound));
+        assert_eq!(
+            not_allowed(table.lookup(None, "POST", "/files/12/x")),
+            ["GET".into(), "HEAD".into(), "OPTIONS".into()]
+        );
+    }
+
+    #[test]
+    fn test_wildcard_fallback_follows_shared_shape_changes() {
+        let router = GlobalRouter::new();
+        for path in ["/files/:name/{*rest}", "/files/:id<int>/{*rest}", "/files/:id<int>/meta"] {
+            router.insert("GET", route("GET", path)).unwrap();
+        }
+        let fallback = |path| router.find(None, "GET", path).map(|m| m.route.original_path.clone());
+
+        // `/files/abc/meta` reaches the `meta` node first, whose only route wants an int.
+        assert_eq!(fallback("/files/abc/meta").as_deref(), Some("/files/:name/{*rest}"));
+        assert_eq!(fallback("/files/12/meta").as_deref(), Some("/files/:id<int>/meta"));
+
+        router.replace("GET", route("GET", "/files/:slug/{*rest}")).unwrap();
+        assert_eq!(fallback("/files/abc/meta").as_deref(), Some("/files/:slug/{*rest}"));
+
+        let removed = route("GET", "/files/:slug/{*rest}");
+        assert!(router.remove(None, "GET", &removed.shape, &removed.params));
+        assert_eq!(fallback("/files/abc/meta"), None);
+        assert_eq!(fallback("/files/12/x").as_deref(), Some("/files/:id<int>/{*rest}"));
+    }
+
+    #[test]
+    fn test_case_insensitive_static_collision() {
+        let mut table = table(&[("GET", "/api")]);
+
+        let mut folded = route("GET", "/api");
+        folded.original_path = "/API".into();
+        folded.case_insensitive = true;
+
+        let err = table.insert("GET", folded).unwrap_err();
+        assert!(err.contains("/API conflicts with previously registered route /api"), "{err}");
+        assert_eq!(table.route_count(), 1);
+        assert_eq!(found(table.lookup(None, "GET", "/api")), "/api");
+    }
+
+    #[test]
+    fn test_static_route_registered_again_replaces_the_first() {
+        let mut table = table(&[("GET", "/api")]);
+
+        let mut again = route("GET", "/api");
+        again.timeout = Some(5);
+        table.insert("GET", again).unwrap();
+
+        assert_eq!(table.route_count(), 1);
+        let RouteLookup::Found(matched) = table.lookup(None, "GET", "/api") else {
+            panic!("/api is not routed");
+        };
+        assert_eq!(matched.route.timeout, Some(5));
+    }
+
+    #[test]
+    fn test_redirect_checks_method_of_target() {
+        for table in [table(&[("GET", "/users")]), with_catch_all(&[("GET", "/users")])] {
+            let resolve = |method, path| table.resolve(None, method, path, TrailingSlash::Redirect);
+
+            assert!(
+                matches!(resolve("GET", "/users/"), RouteLookup::Redirect(to) if to == "/users")
+            );
+            assert!(matches!(resolve("HEAD", "/users/"), RouteLookup::Redirect(_)));
+            assert_eq!(
+                not_allowed(resolve("POST", "/users/")),
+                ["GET".into(), "HEAD".into(), "OPTIONS".into()]
+            );
+        }
+    }
+
+    #[test]
+    fn test_routes_registered_before_the_first_lookup() {
+        let router = GlobalRouter::new();
+        router.insert("GET", route("GET", "/users")).unwrap();
+        router.insert("GET", route("GET", "/users/:id")).unwrap();
+        assert!(router.insert("GET", route("GET", "/users/:name")).is_err());
+        assert!(router.replace("GET", route("GET", "/users/:id")).unwrap());
+
+        let staged = route("GET", "/users");
+        assert!(router.remove(None, "GET", &staged.shape, &staged.params));
+        router.insert("GET", route("GET", "/users")).unwrap();
+
+        let found = |path| router.find(None, "GET", path).map(|m| m.route.original_path.clone());
+        assert_eq!(found("/users").as_deref(), Some("/users"));
+        assert_eq!(found("/users/7").as_deref(), Some("/users/:id"));
+        assert_eq!(router.route_count(), 2);
+
+        // Once published, changes are still seen by the next lookup.
+        router.insert("POST", route("POST", "/users")).unwrap();
+        assert!(router.find(None, "POST", "/users").is_some());
+        router.publish();
+        assert_eq!(router.route_count(), 3);
+    }
+
+    #[test]
+    fn test_rejected_constraint_falls_back_to_other_shapes() {
+        let two = table(&[("GET", "/a/:id<int>/b"), ("GET", "/a/:x/:y")]);
+        let find = |path| two.find(None, "GET", path).unwrap();
+
+        assert_eq!(find("/a/7/b").route.original_path.as_ref(), "/a/:id<int>/b");
+
+        let matched = find("/a/x/b");
+        assert_eq!(matched.route.original_path.as_ref(), "/a/:x/:y");
+        assert_eq!(matched.params.get("x").map(String::as_str), Some("x"));
+        assert_eq!(matched.params.get("y").map(String::as_str), Some("b"));
+
+        // Shapes are tried most specific first, down to the catch-all.
+        let three =
+            table(&[("GET", "/a/:id<int>/b"), ("GET", "/a/:x/:y<int>"), ("GET", "/{*rest}")]);
+        let find = |path| three.find(None, "GET", path).unwrap().route.original_path.clone();
+        assert_eq!(find("/a/x/7").as_ref(), "/a/:x/:y<int>");
+        assert_eq!(find("/a/x/b").as_ref(), "/{*rest}");
+    }
+}
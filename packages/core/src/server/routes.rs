@@ -11,21 +11,38 @@ use napi::{bindgen_prelude::Function, threadsafe_function::ThreadsafeFunction};
 use base64::Engine;
 use base64::engine::general_purpose;
 
-use once_cell::sync::Lazy;
 use serde_json::{Value, from_str, from_value};
 
 use crate::server::context::ContextObject;
-use crate::server::router::GlobalRouter;
+use crate::server::host::HostPattern;
+use crate::server::params::{PathParam, RoutePattern};
+use crate::server::path::fold_case;
+use crate::server::router::{GlobalRouter, HttpRouter, RouteTable};
 use crate::validation::types::SchemaType;
 
 pub type RouteHandler = ThreadsafeFunction<ContextObject, (), ContextObject, napi::Status, false>;
 
 pub struct CompiledRoute {
     pub method: Box<str>,
+    /// Path as registered, e.g. `/users/:id`.
+    pub original_path: Box<str>,
+    /// Path in matchit syntax, e.g. `/users/{id}`.
     pub path: Box<str>,
+    /// Path with positional param names, e.g. `/users/{0}` (see `RoutePattern`).
+    pub shape: Box<str>,
+    /// Params in path order, with their constraints.
+    pub params: Box<[PathParam]>,
     pub segments: Box<[Box<str>]>,
     pub strategy: ResponseStrategy,
     pub schema: Option<RouteSchema>,
+    /// Overrides `ServerOptionsCore.max_request_size` for this route.
+    pub max_request_size: Option<u32>,
+    /// Overrides `ServerOptionsCore.timeout` for this route.
+    pub timeout: Option<u32>,
+    /// Path is stored folded (see `fold_case`) and matched regardless of ASCII case.
+    pub case_insensitive: bool,
+    /// Only requests for a matching host reach the route. `None` serves any host.
+    pub host: Option<HostPattern>,
 }
 
 #[derive(Clone)]
@@ -36,8 +53,6 @@ pub struct RouteSchema {
     pub headers: Option<SchemaType>,
 }
 
-pub static ROUTER: Lazy<GlobalRouter> = Lazy::new(GlobalRouter::new);
-
 #[napi(object)]
 pub struct Route {
     pub path: String,
@@ -47,6 +62,15 @@ pub struct Route {
     pub handler: Function<'static, ContextObject, ()>,
     pub schema: Option<String>,
     pub static_response: Option<String>,
+    pub max_request_size: Option<u32>,
+    pub timeout: Option<u32>,
+    /// Match the path regardless of ASCII case; params keep the request's casing.
+    /// Defaults to `ServerOptionsCore.routing.case_insensitive`.
+    pub case_insensitive: Option<bool>,
+    /// Serve the route only for this host: `api.example.com`, `*.example.com`, or
+    /// `:tenant.example.com` to capture a label as a param. Unset routes serve every
+    /// host that no host-bound route matches.
+    pub host: Option<String>,
 }
 
 #[derive(Clone)]
@@ -56,31 +80,141 @@ pub enum ResponseStrategy {
     ParamTemplate { template: String, params: Vec<String>, headers: HashMap<String, String> },
 }
 
-fn convert_path_to_matchit_format(path: &str) -> String {
-    if !path.contains(':') {
-        return path.to_string();
+impl ResponseStrategy {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ResponseStrategy::Dynamic(_) => "dynamic",
+            ResponseStrategy::FullStatic(_) => "full_static",
+            ResponseStrategy::ParamTemplate { .. } => "param_template",
+        }
     }
+}
 
-    let parts: Vec<&str> = path.split('/').collect();
-    let converted_parts: Vec<String> = parts
-        .iter()
-        .map(|part| {
-            if let Some(stripped) = part.strip_prefix(':') {
-                format!("{{{stripped}}}")
-            } else {
-                part.to_string()
-            }
-        })
-        .collect();
+/// A registered route, as reported by `ServerCore.routes()`.
+#[napi(object)]
+pub struct RouteInfoCore {
+    pub method: String,
+    /// Path as registered, e.g. `/users/:id`.
+    pub path: String,
+    /// Path in matchit syntax, e.g. `/users/{id}`.
+    pub matchit_path: String,
+    /// Matched by exact lookup rather than through the trie.
+    pub is_static: bool,
+    /// How the response is produced: by the JS handler, or by the core alone.
+    #[napi(ts_type = "'dynamic' | 'full_static' | 'param_template'")]
+    pub strategy: String,
+    /// Request parts validated by the route schema.
+    #[napi(ts_type = "Array<'params' | 'query' | 'body' | 'headers'>")]
+    pub schema: Vec<String>,
+    pub max_request_size: Option<u32>,
+    pub timeout: Option<u32>,
+    pub case_insensitive: bool,
+    /// Host pattern the route is bound to.
+    pub host: Option<String>,
+}
+
+/// Number of registered routes, split by how they are matched.
+#[napi(object)]
+pub struct RouteCountCore {
+    pub total: u32,
+    pub static_routes: u32,
+    pub dynamic_routes: u32,
+}
+
+impl CompiledRoute {
+    pub fn info(&self) -> RouteInfoCore {
+        let schema = self
+            .schema
+            .as_ref()
+            .map(|schema| {
+                [
+                    ("params", schema.params.is_some()),
+                    ("query", schema.query.is_some()),
+                    ("body", schema.body.is_some()),
+                    ("headers", schema.headers.is_some()),
+                ]
+                .into_iter()
+                .filter(|(_, present)| *present)
+                .map(|(part, _)| part.to_string())
+                .collect()
+            })
+            .unwrap_or_default();
 
-    converted_parts.join("/")
+        RouteInfoCore {
+            method: self.method.to_string(),
+            path: self.original_path.to_string(),
+            matchit_path: self.path.to_string(),
+            is_static: HttpRouter::is_static_route(&self.path),
+            strategy: self.strategy.kind().to_string(),
+            schema,
+            max_request_size: self.max_request_size,
+            timeout: self.timeout,
+            case_insensitive: self.case_insensitive,
+            host: self.host.as_ref().map(|host| host.as_str().to_string()),
+        }
+    }
 }
 
-pub fn insert_route(route: Route) -> napi::Result<()> {
+pub fn insert_route(router: &GlobalRouter, route: Route) -> napi::Result<()> {
+    let compiled = compile_route(route)?;
+    router.insert(&compiled.method.clone(), compiled).map_err(Error::from_reason)
+}
+
+/// Swap in `route` for the one registered for the same method and path, or add it.
+pub fn replace_route(router: &GlobalRouter, route: Route) -> napi::Result<bool> {
+    let compiled = compile_route(route)?;
+    router.replace(&compiled.method.clone(), compiled).map_err(Error::from_reason)
+}
+
+pub fn remove_route(router: &GlobalRouter, method: &str, path: &str, host: Option<&str>) -> bool {
+    let Ok(host) = host.map(HostPattern::parse).transpose() else {
+        return false;
+    };
+    let host = host.as_ref().map(|h| h.as_str());
+
+    RoutePattern::parse(path)
+        .is_ok_and(|pattern| router.remove(host, method, &pattern.shape, &pattern.params))
+}
+
+/// Build a complete table from `routes` and publish it in one step. Nothing changes if
+/// any route is invalid or conflicts with another.
+pub fn swap_routes(router: &GlobalRouter, routes: Vec<Route>) -> napi::Result<()> {
+    let mut table = RouteTable::new();
+
+    for route in routes {
+        let compiled = compile_route(route)?;
+        table.insert(&compiled.method.clone(), compiled).map_err(Error::from_reason)?;
+    }
+
+    router.swap(table);
+    Ok(())
+}
+
+fn compile_route(route: Route) -> napi::Result<CompiledRoute> {
     let method_key: Box<str> = route.method.clone().into_boxed_str();
-    let converted_path = convert_path_to_matchit_format(&route.path);
+    let case_insensitive = route.case_insensitive.unwrap_or(false);
+
+    let mut pattern = RoutePattern::parse(&route.path).map_err(Error::from_reason)?;
+    if case_insensitive {
+        pattern.path = fold_case(&pattern.path);
+        pattern.shape = fold_case(&pattern.shape);
+    }
 
-    let segments: Vec<Box<str>> = converted_path
+    let host =
+        route.host.as_deref().map(HostPattern::parse).transpose().map_err(Error::from_reason)?;
+    if let Some(name) = host
+        .iter()
+        .flat_map(|host| host.param_names())
+        .find(|name| pattern.params.iter().any(|p| &*p.name == *name))
+    {
+        return Err(Error::from_reason(format!(
+            "Param '{name}' of route {} appears in both its host and its path",
+            route.path
+        )));
+    }
+
+    let segments: Vec<Box<str>> = pattern
+        .path
         .split('/')
         .filter(|s| !s.is_empty())
         .map(|s| s.to_string().into_boxed_str())
@@ -164,14 +298,18 @@ pub fn insert_route(route: Route) -> napi::Result<()> {
         None
     };
 
-    let compiled = CompiledRoute {
-        method: method_key.clone(),
-        path: converted_path.into_boxed_str(),
+    Ok(CompiledRoute {
+        method: method_key,
+        original_path: route.path.into_boxed_str(),
+        path: pattern.path.into_boxed_str(),
+        shape: pattern.shape.into_boxed_str(),
+        params: pattern.params.into_boxed_slice(),
         segments: segments.into_boxed_slice(),
         strategy,
         schema,
-    };
-
-    ROUTER.insert(&route.method, compiled).map_err(Error::from_reason)?;
-    Ok(())
+        max_request_size: route.max_request_size,
+        timeout: route.timeout,
+        case_insensitive,
+        host,
+    })
 }
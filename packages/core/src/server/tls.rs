@@ -0,0 +1,103 @@
+use napi::{
+    Error,
+    bindgen_prelude::{Buffer, Either, FromNapiValue, ToNapiValue},
+    sys,
+};
+
+use rustls::{
+    ServerConfig,
+    crypto::ring,
+    pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject},
+};
+use tokio_rustls::TlsAcceptor;
+
+use std::{fs, sync::Arc};
+
+/// Certificate and private key used to terminate TLS.
+///
+/// Each side can be given either inline as PEM text or a buffer holding it (`cert` /
+/// `key`), or as a path to a PEM file (`cert_path` / `key_path`). Inline values win when
+/// both are set.
+#[derive(Clone)]
+#[napi(object)]
+pub struct TlsOptionsCore {
+    #[napi(ts_type = "string | Buffer")]
+    pub cert: Option<Pem>,
+    #[napi(ts_type = "string | Buffer")]
+    pub key: Option<Pem>,
+    pub cert_path: Option<String>,
+    pub key_path: Option<String>,
+}
+
+/// In-memory PEM contents, passed from JavaScript as a string or a `Buffer`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pem(pub Vec<u8>);
+
+impl FromNapiValue for Pem {
+    unsafe fn from_napi_value(env: sys::napi_env, napi_val: sys::napi_value) -> napi::Result<Self> {
+        let value = unsafe { Either::<String, Buffer>::from_napi_value(env, napi_val)? };
+
+        Ok(match value {
+            Either::A(text) => Pem::from(text),
+            Either::B(buffer) => Pem::from(buffer),
+        })
+    }
+}
+
+impl From<String> for Pem {
+    fn from(text: String) -> Self {
+        Pem(text.into_bytes())
+    }
+}
+
+impl From<Buffer> for Pem {
+    fn from(buffer: Buffer) -> Self {
+        Pem(buffer.to_vec())
+    }
+}
+
+impl ToNapiValue for Pem {
+    unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> napi::Result<sys::napi_value> {
+        unsafe { Buffer::to_napi_value(env, Buffer::from(val.0)) }
+    }
+}
+
+pub fn build_tls_acceptor(options: &TlsOptionsCore, http2: bool) -> napi::Result<TlsAcceptor> {
+    let cert_pem = read_pem(options.cert.as_ref(), options.cert_path.as_deref(), "certificate")?;
+    let key_pem = read_pem(options.key.as_ref(), options.key_path.as_deref(), "private key")?;
+
+    let certs = CertificateDer::pem_slice_iter(&cert_pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| Error::from_reason(format!("Invalid TLS certificate: {e}")))?;
+
+    if certs.is_empty() {
+        return Err(Error::from_reason("No certificates found in TLS certificate PEM"));
+    }
+
+    let key = PrivateKeyDer::from_pem_slice(&key_pem)
+        .map_err(|e| Error::from_reason(format!("Invalid TLS private key: {e}")))?;
+
+    let mut config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| Error::from_reason(format!("Invalid TLS configuration: {e}")))?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| Error::from_reason(format!("Invalid TLS configuration: {e}")))?;
+
+    config.alpn_protocols =
+        if http2 { vec![b"h2".to_vec(), b"http/1.1".to_vec()] } else { vec![b"http/1.1".to_vec()] };
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+fn read_pem(inline: Option<&Pem>, path: Option<&str>, what: &str) -> napi::Result<Vec<u8>> {
+    if let Some(pem) = inline {
+        return Ok(pem.0.clone());
+    }
+
+    match path {
+        Some(path) => fs::read(path)
+            .map_err(|e| Error::from_reason(format!("Failed to read TLS {what} '{path}': {e}"))),
+        None => Err(Error::from_reason(format!("Missing TLS {what}"))),
+    }
+}
@@ -0,0 +1,165 @@
+#[cfg(test)]
+mod tests {
+    use super::super::connection::*;
+    use super::super::core::ServerOptionsCore;
+    use super::super::router::GlobalRouter;
+    use super::super::tls::*;
+    use crate::http::request::RequestCore;
+    use http_body_util::{BodyExt, Empty, Full};
+    use hyper::{Request, Response, body::Bytes, client::conn, service::service_fn};
+    use hyper_util::rt::TokioIo;
+    use napi::bindgen_prelude::Buffer;
+    use rustls::{
+        ClientConfig, RootCertStore,
+        crypto::ring,
+        pki_types::{CertificateDer, ServerName, pem::PemObject},
+    };
+    use std::{convert::Infallible, sync::Arc, time::Duration};
+    use tokio::sync::watch;
+    use tokio_rustls::TlsConnector;
+
+    const CERT: &str = include_str!("../../fixtures/localhost.crt");
+    const KEY: &str = include_str!("../../fixtures/localhost.key");
+
+    fn options(cert: Pem, key: Pem) -> TlsOptionsCore {
+        TlsOptionsCore { cert: Some(cert), key: Some(key), cert_path: None, key_path: None }
+    }
+
+    fn localhost() -> TlsOptionsCore {
+        options(Pem::from(CERT.to_string()), Pem::from(KEY.to_string()))
+    }
+
+    fn error(options: &TlsOptionsCore) -> String {
+        build_tls_acceptor(options, true).err().expect("the options are invalid").reason.clone()
+    }
+
+    /// A client trusting only the fixture certificate, offering `alpn`.
+    fn connector(alpn: &[&[u8]]) -> TlsConnector {
+        let mut roots = RootCertStore::empty();
+        roots.add(CertificateDer::from_pem_slice(CERT.as_bytes()).unwrap()).unwrap();
+
+        let mut config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        config.alpn_protocols = alpn.iter().map(|p| p.to_vec()).collect();
+
+        TlsConnector::from(Arc::new(config))
+    }
+
+    #[test]
+    fn test_pem_from_string_and_buffer() {
+        assert!(build_tls_acceptor(&localhost(), true).is_ok());
+
+        let buffers = options(
+            Pem::from(Buffer::from(CERT.as_bytes().to_vec())),
+            Pem::from(Buffer::from(KEY.as_bytes().to_vec())),
+        );
+        assert!(build_tls_acceptor(&buffers, true).is_ok());
+    }
+
+    #[test]
+    fn test_pem_from_files() {
+        let fixture = |name: &str| format!("{}/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+        let files = TlsOptionsCore {
+            cert: None,
+            key: None,
+            cert_path: Some(fixture("localhost.crt")),
+            key_path: Some(fixture("localhost.key")),
+        };
+        assert!(build_tls_acceptor(&files, true).is_ok());
+
+        let missing = TlsOptionsCore { cert_path: Some(fixture("missing.crt")), ..files };
+        assert!(error(&missing).starts_with("Failed to read TLS certificate"));
+    }
+
+    #[test]
+    fn test_invalid_pem() {
+        let junk = || Pem::from("not a pem".to_string());
+
+        let bad_cert = options(junk(), Pem::from(KEY.to_string()));
+        assert_eq!(error(&bad_cert), "No certificates found in TLS certificate PEM");
+
+        let bad_key = options(Pem::from(CERT.to_string()), junk());
+        assert!(error(&bad_key).starts_with("Invalid TLS private key"));
+
+        let no_key = TlsOptionsCore { key: None, ..localhost() };
+        assert_eq!(error(&no_key), "Missing TLS private key");
+    }
+
+    #[test]
+    fn test_alpn_protocols() {
+        let with_h2 = build_tls_acceptor(&localhost(), true).unwrap();
+        assert_eq!(with_h2.config().alpn_protocols, [b"h2".to_vec(), b"http/1.1".to_vec()]);
+
+        let without_h2 = build_tls_acceptor(&localhost(), false).unwrap();
+        assert_eq!(without_h2.config().alpn_protocols, [b"http/1.1".to_vec()]);
+    }
+
+    #[tokio::test]
+    async fn test_handshake_marks_requests_secure() {
+        let acceptor = build_tls_acceptor(&localhost(), true).unwrap();
+        let (client, server) = tokio::io::duplex(16 * 1024);
+
+        let serving = tokio::spawn(async move {
+            let (stream, conn) =
+                accept_tls(&acceptor, server, ConnectionInfo::default(), Duration::from_secs(5))
+                    .await
+                    .expect("handshake completes");
+
+            let service = service_fn(move |req: Request<_>| {
+                let (parts, _) = req.into_parts();
+                let core = RequestCore::new(&parts, &conn, false);
+                let body = format!("{} {}", core.protocol, core.secure);
+                async move { Ok::<_, Infallible>(Response::new(Full::new(Bytes::from(body)))) }
+            });
+            hyper::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+        });
+
+        let domain = ServerName::try_from("localhost").unwrap();
+        let tls = connector(&[b"http/1.1"]).connect(domain, client).await.unwrap();
+        assert_eq!(tls.get_ref().1.alpn_protocol(), Some(&b"http/1.1"[..]));
+
+        let (mut sender, connection) = conn::http1::handshake(TokioIo::new(tls)).await.unwrap();
+        tokio::spawn(connection);
+
+        let request = Request::get("/").header("host", "localhost").body(Empty::<Bytes>::new());
+        let response = sender.send_request(request.unwrap()).await.unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"https true");
+        serving.abort();
+    }
+
+    #[tokio::test]
+    async fn test_stalled_handshake_times_out() {
+        let config = ServerOptionsCore { header_read_timeout: Some(50), ..Default::default() };
+        let (_shutdown_tx, shutdown_rx) = watch::channel(Shutdown::Running);
+        let ctx = ServeContext {
+            builder: Arc::new(build_connection_builder(&config)),
+            router: Arc::new(GlobalRouter::new()),
+            config: Arc::new(config),
+            cors: None,
+            shutdown: shutdown_rx,
+        };
+
+        let tracker = Arc::new(ConnectionTracker::default());
+        let acceptor = build_tls_acceptor(&localhost(), true).unwrap();
+
+        // The client connects but never sends its ClientHello.
+        let (_client, server) = tokio::io::duplex(16 * 1024);
+        let serving = serve_stream(
+            server,
+            Some(acceptor),
+            false,
+            ctx,
+            ConnectionInfo::default(),
+            tracker.track(),
+        );
+
+        tokio::time::timeout(Duration::from_secs(5), serving).await.expect("handshake timed out");
+        assert_eq!(tracker.active(), 0);
+    }
+}
@@ -1,8 +1,8 @@
 pub mod parser;
 pub mod parser_tests;
-pub mod tests;
 pub mod types;
 pub mod validators;
+pub mod validators_tests;
 
 pub use parser::*;
 pub use types::*;
@@ -0,0 +1,31 @@
+import type { MiddlewareDefinition, RouteOptions } from "@kitojs/types";
+
+/**
+ * Overrides server options for a single route. Pass the result with the
+ * route's middlewares.
+ *
+ * @param options - Options that apply to this route instead of the server's
+ * @returns Definition to put in the route's middleware list
+ *
+ * @example
+ * ```typescript
+ * import { server, routeOptions } from 'kitojs';
+ *
+ * const app = server({ maxRequestSize: 1024 * 1024 });
+ *
+ * app.post('/uploads', [routeOptions({ maxRequestSize: 50 * 1024 * 1024 })], ctx => {
+ *   ctx.res.send('stored');
+ * });
+ *
+ * app.get('/reports', [routeOptions({ timeout: 60_000 })], async ctx => {
+ *   ctx.res.json(await buildReport());
+ * });
+ * ```
+ */
+export function routeOptions(options: RouteOptions): MiddlewareDefinition {
+  return {
+    type: "options",
+    options,
+    global: false,
+  };
+}
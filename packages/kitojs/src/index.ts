@@ -78,6 +78,7 @@ export * from "./schemas/builders";
 
 export * from "./helpers/schema";
 export * from "./helpers/middleware";
+export * from "./helpers/routeOptions";
 
 export * from "./server/server";
 export * from "./server/router";
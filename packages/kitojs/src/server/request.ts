@@ -2,6 +2,7 @@
 import type {
   CommonHeaderNames,
   KitoRequest,
+  PeerCredentials,
   RequestHeaders,
 } from "@kitojs/types";
 import {
@@ -12,6 +13,7 @@ import {
   getAllQuery,
   getParam,
   getAllParams,
+  getNumericParams,
   getCookie,
   getAllCookies,
   getMethod,
@@ -22,6 +24,7 @@ import {
   getHostname,
   getIp,
   getIps,
+  getPeerCredentials,
   getSecure,
   getXhr,
 } from "@kitojs/kito-core";
@@ -35,6 +38,7 @@ export class RequestBuilder implements KitoRequest {
   private _headers?: Record<string, string>;
   private _query?: Record<string, string | string[]>;
   private _params?: Record<string, string>;
+  private _numericParams?: Record<string, number>;
   private _cookies?: Record<string, string>;
   private _method?: string;
   private _url?: string;
@@ -44,6 +48,7 @@ export class RequestBuilder implements KitoRequest {
   private _hostname?: string;
   private _ip?: string;
   private _ips?: string[];
+  private _peerCredentials?: PeerCredentials | null;
   private _secure?: boolean;
   private _xhr?: boolean;
 
@@ -130,6 +135,13 @@ export class RequestBuilder implements KitoRequest {
     return this._params;
   }
 
+  get numericParams(): Record<string, number> {
+    if (!this._numericParams) {
+      this._numericParams = getNumericParams(this.core);
+    }
+    return this._numericParams;
+  }
+
   param(name: string): string | undefined {
     return getParam(this.core, name) ?? undefined;
   }
@@ -201,6 +213,13 @@ export class RequestBuilder implements KitoRequest {
     return this._ips;
   }
 
+  get peerCredentials(): PeerCredentials | null {
+    if (this._peerCredentials === undefined) {
+      this._peerCredentials = getPeerCredentials(this.core) ?? null;
+    }
+    return this._peerCredentials;
+  }
+
   get secure(): boolean {
     if (this._secure === undefined) {
       this._secure = getSecure(this.core);
@@ -95,6 +95,40 @@ export class KitoRouter<TExtensions = {}>
     return this;
   }
 
+  /**
+   * Binds the routes of a sub-router to a host. Requests for other hosts
+   * never reach them; routes without a host serve whatever no host-bound
+   * route matches.
+   *
+   * @param pattern - Exact host (`api.example.com`), wildcard
+   * (`*.example.com`), or a pattern capturing a label as a param
+   * (`:tenant.example.com`)
+   * @param router - Router instance whose routes are bound to the host
+   * @returns The router instance for chaining
+   *
+   * @example
+   * ```typescript
+   * const tenant = router();
+   * tenant.get('/', ({ req, res }) => res.send(req.params.tenant));
+   *
+   * const app = server();
+   * app.host(':tenant.example.com', tenant);
+   * ```
+   */
+  host(pattern: string, router: KitoRouter<TExtensions>): this {
+    const subRouterMiddlewares = router.getMiddlewares();
+
+    const hostRoutes = router.getRoutes().map((route) => ({
+      ...route,
+      host: pattern,
+      middlewares: [...subRouterMiddlewares, ...route.middlewares],
+    }));
+
+    this.routes.push(...hostRoutes);
+
+    return this;
+  }
+
   /**
    * Registers a GET route.
    */
@@ -765,7 +799,7 @@ export class KitoRouter<TExtensions = {}>
     return this.middlewares;
   }
 
-  private normalizePath(path: string): string {
+  protected normalizePath(path: string): string {
     let normalized = path.startsWith("/") ? path : `/${path}`;
     if (normalized.length > 1 && normalized.endsWith("/")) {
       normalized = normalized.slice(0, -1);
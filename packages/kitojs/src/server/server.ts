@@ -1,5 +1,6 @@
 // biome-ignore assist/source/organizeImports: ...
 import type {
+  ConnectionStats,
   HttpMethod,
   MiddlewareDefinition,
   SchemaDefinition,
@@ -7,9 +8,19 @@ import type {
   RouteHandler,
   KitoContext,
   KitoServerInstance,
+  ListenCallback,
+  ListenerOptions,
+  RouteCount,
+  RouteInfo,
+  RouteOptions,
+  ServerAddress,
 } from "@kitojs/types";
 
-import { ServerCore, type ServerOptionsCore } from "@kitojs/kito-core";
+import {
+  ServerCore,
+  type Route as CoreRoute,
+  type ServerOptionsCore,
+} from "@kitojs/kito-core";
 import { RequestBuilder } from "./request";
 import { ResponseBuilder } from "./response";
 
@@ -43,6 +54,7 @@ export class KitoServer<TExtensions = {}>
   private coreServer: ServerCore;
   // biome-ignore lint/suspicious/noExplicitAny: ...
   private extensionFn?: (ctx: any) => void;
+  private catchAllRegistered = false;
 
   /**
    * Creates a new Kito server instance.
@@ -51,8 +63,25 @@ export class KitoServer<TExtensions = {}>
    * @param options.port - Port to listen on (default: 3000)
    * @param options.host - Host to bind to (default: "0.0.0.0")
    * @param options.trustProxy - Trust X-Forwarded-* headers
-   * @param options.maxRequestSize - Maximum request body size in bytes
-   * @param options.timeout - Request timeout in milliseconds
+   * @param options.methodNotAllowed - Answer 405 with `Allow` for methods a path is not routed for (default: true)
+   * @param options.routing - Trailing-slash policy and path normalization before routing
+   * @param options.cors - Native CORS: preflights answered by the router, headers on every response
+   * @param options.proxyProtocol - Read the client address from a PROXY protocol header
+   * @param options.maxRequestSize - Maximum request body size in bytes (larger bodies get 413)
+   * @param options.timeout - Handler response timeout in milliseconds
+   * @param options.headerReadTimeout - Request header read timeout in milliseconds
+   * @param options.bodyReadTimeout - Request body read timeout in milliseconds
+   * @param options.reusePort - Let other processes bind the same port (`SO_REUSEPORT`)
+   * @param options.socket - Low-level socket tuning (backlog, keep-alive, buffers, ...)
+   * @param options.tls - Certificate and key to serve HTTPS directly
+   * @param options.http1 - HTTP/1 keep-alive, header limits and pipelining
+   * @param options.http2 - HTTP/2 negotiation and tuning
+   * @param options.keepAliveTimeout - Idle connection timeout in milliseconds
+   * @param options.maxRequestsPerConnection - Requests served before a connection is closed
+   * @param options.shutdownTimeout - Maximum time in milliseconds `shutdown()` waits for connections to drain
+   * @param options.maxConnections - Maximum number of open connections
+   * @param options.maxConnectionsPerIp - Maximum number of open connections per client IP
+   * @param options.connectionLimitAction - Pause accepting or reject with 503 at `maxConnections`
    */
   constructor(options?: ServerOptions) {
     super();
@@ -61,10 +90,30 @@ export class KitoServer<TExtensions = {}>
       port: options?.unixSocket ? undefined : options?.port,
       host: options?.unixSocket ? undefined : options?.host,
       unixSocket: options?.unixSocket,
+      unixSocketMode: options?.unixSocketMode,
+      unixSocketGroup: options?.unixSocketGroup,
+      listeners: options?.listeners,
+      socketActivation: options?.socketActivation,
       reusePort: options?.reusePort,
+      socket: options?.socket,
       trustProxy: options?.trustProxy,
+      methodNotAllowed: options?.methodNotAllowed,
+      routing: options?.routing,
+      cors: options?.cors,
+      proxyProtocol: options?.proxyProtocol,
       maxRequestSize: options?.maxRequestSize,
       timeout: options?.timeout,
+      headerReadTimeout: options?.headerReadTimeout,
+      bodyReadTimeout: options?.bodyReadTimeout,
+      tls: options?.tls,
+      http1: options?.http1,
+      http2: options?.http2,
+      keepAliveTimeout: options?.keepAliveTimeout,
+      maxRequestsPerConnection: options?.maxRequestsPerConnection,
+      shutdownTimeout: options?.shutdownTimeout,
+      maxConnections: options?.maxConnections,
+      maxConnectionsPerIp: options?.maxConnectionsPerIp,
+      connectionLimitAction: options?.connectionLimitAction,
     });
   }
 
@@ -150,10 +199,143 @@ export class KitoServer<TExtensions = {}>
     return this;
   }
 
+  override host(pattern: string, router: KitoRouter<TExtensions>): this {
+    const routeIndex = this.routes.length;
+    super.host(pattern, router);
+
+    for (let i = routeIndex; i < this.routes.length; i++) {
+      this.registerRouteWithCore(this.routes[i]);
+    }
+
+    return this;
+  }
+
   private registerRouteWithCore(
     // biome-ignore lint/suspicious/noExplicitAny: ...
     route: any,
   ): void {
+    this.coreServer.addRoute(this.toCoreRoute(route));
+  }
+
+  /**
+   * Removes a route while the server is running. Requests already being
+   * handled by it finish normally.
+   *
+   * @param method - HTTP method the route was registered for
+   * @param path - Path exactly as it was registered (e.g. `/users/:id`)
+   * @param host - Host pattern the route was bound to with `host()`, if any
+   * @returns Whether a route was removed
+   */
+  removeRoute(method: HttpMethod, path: string, host?: string): boolean {
+    const normalizedPath = this.normalizePath(path);
+    this.routes = this.routes.filter(
+      (route) =>
+        route.method !== method ||
+        route.path !== normalizedPath ||
+        route.host !== host,
+    );
+
+    return this.coreServer.removeRoute(method, normalizedPath, host);
+  }
+
+  /**
+   * Replaces the handler of a route while the server is running, or adds the
+   * route if it does not exist yet.
+   *
+   * @param method - HTTP method of the route
+   * @param path - Path exactly as it was registered (e.g. `/users/:id`)
+   * @param middlewaresOrHandler - Route middlewares, or the new handler
+   * @param handler - The new handler when middlewares are given
+   * @returns Whether an existing route was replaced
+   *
+   * @example
+   * ```typescript
+   * app.replaceRoute("GET", "/status", ctx => {
+   *   ctx.res.send("maintenance");
+   * });
+   * ```
+   */
+  replaceRoute(
+    method: HttpMethod,
+    path: string,
+    middlewaresOrHandler:
+      | (MiddlewareDefinition | SchemaDefinition)[]
+      | RouteHandler<SchemaDefinition, TExtensions>,
+    handler?: RouteHandler<SchemaDefinition, TExtensions>,
+  ): boolean {
+    super.addRoute(method, path, middlewaresOrHandler, handler);
+    // biome-ignore lint/style/noNonNullAssertion: just pushed by addRoute
+    const route = this.routes.pop()!;
+
+    const replaced = this.coreServer.replaceRoute(this.toCoreRoute(route));
+
+    this.routes = this.routes.filter(
+      (existing) =>
+        existing.method !== route.method || existing.path !== route.path,
+    );
+    this.routes.push(route);
+
+    return replaced;
+  }
+
+  /**
+   * Replaces every route with the routes of `router` in one atomic step, e.g.
+   * to hot-reload a route module. Global middlewares of the server still
+   * apply. If any route is invalid, the current routes stay in place.
+   *
+   * @param router - Router holding the complete new set of routes
+   *
+   * @example
+   * ```typescript
+   * const next = router();
+   * next.get("/", ctx => ctx.res.send("v2"));
+   *
+   * app.swapRoutes(next);
+   * ```
+   */
+  swapRoutes(router: KitoRouter<TExtensions>): void {
+    const previous = this.routes;
+    this.routes = [];
+    super.mount("/", router);
+
+    try {
+      const routes = this.routes.map((route) => this.toCoreRoute(route));
+      if (this.catchAllRegistered) {
+        routes.push(...this.catchAllRoutes());
+      }
+
+      this.coreServer.swapRoutes(routes);
+    } catch (error) {
+      this.routes = previous;
+      throw error;
+    }
+  }
+
+  /**
+   * Lists the routes registered with the core, ordered by path and method,
+   * including the catch-all route added for global middlewares.
+   *
+   * @example
+   * ```typescript
+   * console.table(app.listRoutes());
+   * ```
+   */
+  listRoutes(): RouteInfo[] {
+    return this.coreServer.routes() as RouteInfo[];
+  }
+
+  /**
+   * Returns the number of registered routes, split into static paths and
+   * patterns.
+   */
+  routeCount(): RouteCount {
+    return this.coreServer.routeCount();
+  }
+
+  private toCoreRoute(
+    // biome-ignore lint/suspicious/noExplicitAny: ...
+    route: any,
+  ): CoreRoute {
     let finalHandler: RouteHandler<SchemaDefinition, TExtensions>;
     let middlewares: (MiddlewareDefinition | SchemaDefinition)[] = [];
 
@@ -162,9 +344,12 @@ export class KitoServer<TExtensions = {}>
 
     const routeMiddlewares: MiddlewareDefinition[] = [];
     let routeSchema: SchemaDefinition | undefined;
+    let options: RouteOptions = {};
 
     for (const item of middlewares) {
-      if (this.isSchemaDefinition(item)) {
+      if ((item as MiddlewareDefinition).type === "options") {
+        options = { ...options, ...(item as MiddlewareDefinition).options };
+      } else if (this.isSchemaDefinition(item)) {
         routeSchema = item as SchemaDefinition;
         routeMiddlewares.push({
           type: "schema",
@@ -215,13 +400,17 @@ export class KitoServer<TExtensions = {}>
         ? JSON.stringify(staticResponse)
         : undefined;
 
-    this.coreServer.addRoute({
+    return {
       method: route.method,
       path: route.path,
+      host: route.host,
       handler: routeHandler,
       schema: schemaJson,
       staticResponse: staticResponseJson,
-    });
+      maxRequestSize: options.maxRequestSize,
+      timeout: options.timeout,
+      caseInsensitive: options.caseInsensitive,
+    };
   }
 
   private serializeSchema(schema: SchemaDefinition): string {
@@ -249,12 +438,25 @@ export class KitoServer<TExtensions = {}>
   }
 
   private registerCatchAllRoute(): void {
+    this.catchAllRegistered = true;
+
+    for (const route of this.catchAllRoutes()) {
+      try {
+        this.coreServer.addRoute(route);
+      } catch (e) {
+        // Suppress conflict error
+      }
+    }
+  }
+
+  private catchAllRoutes(): CoreRoute[] {
     const hasCatchAll = this.routes.some(
       (route) =>
-        route.path === "{*path}" || route.path === "/*" || route.path === "*",
+        !route.host &&
+        (route.path === "{*path}" || route.path === "/*" || route.path === "*"),
     );
 
-    if (hasCatchAll) return;
+    if (hasCatchAll) return [];
 
     const catchAllHandler: RouteHandler<SchemaDefinition, TExtensions> = (
       ctx,
@@ -284,29 +486,23 @@ export class KitoServer<TExtensions = {}>
       await fusedHandler(context);
     };
 
+    // HEAD is left to the core, which answers it from the GET route.
     const methods: HttpMethod[] = [
       "GET",
       "POST",
       "PUT",
       "DELETE",
       "PATCH",
-      "HEAD",
       "OPTIONS",
     ];
 
-    for (const method of methods) {
-      try {
-        this.coreServer.addRoute({
-          method,
-          path: "/{*path}",
-          handler: routeHandler,
-          schema: undefined,
-          staticResponse: undefined,
-        });
-      } catch (e) {
-        // Suppress conflict error
-      }
-    }
+    return methods.map((method) => ({
+      method,
+      path: "/{*path}",
+      handler: routeHandler,
+      schema: undefined,
+      staticResponse: undefined,
+    }));
   }
 
   private fuseMiddlewares<TSchema extends SchemaDefinition>(
@@ -376,31 +572,50 @@ export class KitoServer<TExtensions = {}>
    * - `port` and `host` are ignored
    * - The server binds exclusively to the provided socket path
    *
-   * You may also pass a callback as the last argument in all forms.
+   * **4. Listening on several addresses at once**
+   * ```ts
+   * app.listen({
+   *   listeners: [
+   *     { port: 8080, host: "0.0.0.0" },
+   *     { port: 8080, host: "::1" },
+   *     { unixSocket: "/run/kito-admin.sock" },
+   *   ],
+   * });
+   * ```
+   *
+   * When `listeners` is non-empty it replaces `port`, `host` and `unixSocket`.
+   *
+   * You may also pass a callback as the last argument in all forms. It receives
+   * the bound addresses, which include the real port when listening on port `0`.
    *
    * @param portOrCallbackOrOptions Port number, callback, or a full `ServerOptions` object.
    * @param hostOrCallback Hostname or callback.
    * @param maybeCallback Optional callback executed once the server starts.
    * @returns The resolved server configuration.
+   * @throws An error whose `code` is `EADDRINUSE`, `EACCES`, `EADDRNOTAVAIL`
+   * or `ERR_INVALID_HOST` when the server cannot listen, or `ERR_SOCKET_ACTIVATION`
+   * when the sockets passed by systemd are already in use by an earlier start.
    */
   async listen(
-    portOrCallbackOrOptions?: number | (() => void) | ServerOptions,
-    hostOrCallback?: string | (() => void),
-    maybeCallback?: () => void,
+    portOrCallbackOrOptions?: number | ListenCallback | ServerOptions,
+    hostOrCallback?: string | ListenCallback,
+    maybeCallback?: ListenCallback,
   ): Promise<ServerOptionsCore> {
     let port: number | undefined;
     let host: string | undefined;
     let unixSocket: string | undefined;
+    let listeners: ListenerOptions[] | undefined;
     let reusePort: boolean | undefined;
-    let ready: (() => void) | undefined;
+    let ready: ListenCallback | undefined;
 
     if (typeof portOrCallbackOrOptions === "object") {
       const options = portOrCallbackOrOptions;
       port = options.port;
       host = options.host;
       unixSocket = options.unixSocket;
+      listeners = options.listeners;
       reusePort = options.reusePort;
-      ready = hostOrCallback as (() => void) | undefined;
+      ready = hostOrCallback as ListenCallback | undefined;
     } else if (typeof portOrCallbackOrOptions === "function") {
       ready = portOrCallbackOrOptions;
     } else {
@@ -414,6 +629,7 @@ export class KitoServer<TExtensions = {}>
     }
 
     const finalUnixSocket = unixSocket ?? this.serverOptions.unixSocket;
+    const finalListeners = listeners ?? this.serverOptions.listeners;
     const finalReusePort = reusePort ?? this.serverOptions.reusePort ?? false;
     const finalPort = finalUnixSocket
       ? undefined
@@ -430,24 +646,112 @@ export class KitoServer<TExtensions = {}>
       port: finalPort,
       host: finalHost,
       unixSocket: finalUnixSocket,
+      unixSocketMode: this.serverOptions.unixSocketMode,
+      unixSocketGroup: this.serverOptions.unixSocketGroup,
+      listeners: finalListeners,
+      socketActivation: this.serverOptions.socketActivation,
       reusePort: finalReusePort,
+      socket: this.serverOptions.socket,
       trustProxy: this.serverOptions.trustProxy,
+      methodNotAllowed: this.serverOptions.methodNotAllowed,
+      routing: this.serverOptions.routing,
+      cors: this.serverOptions.cors,
+      proxyProtocol: this.serverOptions.proxyProtocol,
       maxRequestSize: this.serverOptions.maxRequestSize,
       timeout: this.serverOptions.timeout,
+      headerReadTimeout: this.serverOptions.headerReadTimeout,
+      bodyReadTimeout: this.serverOptions.bodyReadTimeout,
+      tls: this.serverOptions.tls,
+      http1: this.serverOptions.http1,
+      http2: this.serverOptions.http2,
+      keepAliveTimeout: this.serverOptions.keepAliveTimeout,
+      maxRequestsPerConnection: this.serverOptions.maxRequestsPerConnection,
+      shutdownTimeout: this.serverOptions.shutdownTimeout,
+      maxConnections: this.serverOptions.maxConnections,
+      maxConnectionsPerIp: this.serverOptions.maxConnectionsPerIp,
+      connectionLimitAction: this.serverOptions.connectionLimitAction,
     };
 
     this.coreServer.setConfig(configuration);
-    await this.coreServer.start(ready);
+
+    try {
+      await this.coreServer.start(ready);
+    } catch (error) {
+      throw withListenErrorCode(error);
+    }
 
     return configuration;
   }
 
   /**
-   * Closes the server and stops accepting new connections.
+   * Returns the addresses the server is listening on.
+   * Useful with `port: 0` to discover the ephemeral port that was assigned.
+   *
+   * @returns Bound addresses, or an empty array when the server is not listening
+   */
+  addresses(): ServerAddress[] {
+    return this.coreServer.addresses() as ServerAddress[];
+  }
+
+  /**
+   * Returns connection counters: open connections, connections rejected by
+   * the connection limits, and the number of distinct client IPs.
+   */
+  connectionStats(): ConnectionStats {
+    return this.coreServer.connectionStats();
+  }
+
+  /**
+   * Returns the number of open connections from a client IP.
+   *
+   * @param ip - IPv4 or IPv6 address
+   */
+  connectionsFrom(ip: string): number {
+    return this.coreServer.connectionsFrom(ip);
+  }
+
+  /**
+   * Closes the server immediately, dropping any open connections.
    */
   close(): void {
     this.coreServer.close();
   }
+
+  /**
+   * Gracefully shuts the server down.
+   *
+   * Stops accepting new connections and lets open ones finish their in-flight
+   * requests. Connections still open after `timeout` milliseconds are dropped.
+   *
+   * @param timeout - Drain timeout in milliseconds (default: `shutdownTimeout` option, or 10000)
+   * @returns A promise that resolves once every connection has closed
+   *
+   * @example
+   * ```typescript
+   * process.on("SIGTERM", async () => {
+   *   await app.shutdown(5000);
+   *   process.exit(0);
+   * });
+   * ```
+   */
+  async shutdown(timeout?: number): Promise<void> {
+    await this.coreServer.shutdown(timeout);
+  }
+}
+
+/**
+ * Copies the code prefixed to core listen errors (e.g. `EADDRINUSE: ...`)
+ * onto `error.code`, matching the shape of Node's own `listen` errors.
+ */
+function withListenErrorCode(error: unknown): unknown {
+  if (error instanceof Error) {
+    const match = /^([A-Z_]+): /.exec(error.message);
+    if (match) {
+      Object.assign(error, { code: match[1] });
+    }
+  }
+
+  return error;
 }
 
 /**
@@ -26,6 +26,7 @@ export type {
   HttpMethod,
   RouteDefinition,
   MiddlewareDefinition,
+  RouteOptions,
   RouteChain,
   KitoRouterInstance,
   ServerOptions,
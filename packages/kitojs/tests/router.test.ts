@@ -552,4 +552,25 @@ describe("Router", () => {
       expect(mwCalled).toHaveBeenCalled();
     });
   });
+
+  describe("Host Binding", () => {
+    it("should bind sub-router routes to a host pattern", () => {
+      const mw = middleware((_ctx, next) => next());
+      const tenant = router();
+      tenant.use(mw);
+      tenant.get("/", ({ res }) => res.send("tenant"));
+
+      const app = server();
+      app.get("/", ({ res }) => res.send("default"));
+      app.host(":tenant.example.com", tenant);
+
+      // biome-ignore lint/complexity/useLiteralKeys: ...
+      const routes = app["routes"].filter((r) => r.path === "/");
+
+      expect(routes).toHaveLength(2);
+      expect(routes[0].host).toBeUndefined();
+      expect(routes[1].host).toBe(":tenant.example.com");
+      expect(routes[1].middlewares).toHaveLength(1);
+    });
+  });
 });
@@ -1,6 +1,6 @@
 // biome-ignore assist/source/organizeImports: ...
 import { describe, it, expect, beforeEach, afterEach } from "vitest";
-import { server, middleware, schema, t } from "../src";
+import { server, middleware, routeOptions, router, schema, t } from "../src";
 
 describe("Server", () => {
   let app: ReturnType<typeof server>;
@@ -107,6 +107,128 @@ describe("Server", () => {
     });
   });
 
+  describe("Route Updates", () => {
+    it("should remove a registered route", () => {
+      app.get("/users/:id", (ctx) => {
+        ctx.res.send("user");
+      });
+
+      expect(app.removeRoute("GET", "/users/:id")).toBe(true);
+      expect(app.removeRoute("GET", "/users/:id")).toBe(false);
+    });
+
+    it("should replace a route handler", () => {
+      app.get("/status", (ctx) => {
+        ctx.res.send("ok");
+      });
+
+      expect(
+        app.replaceRoute("GET", "/status", (ctx) => {
+          ctx.res.send("maintenance");
+        }),
+      ).toBe(true);
+      expect(
+        app.replaceRoute("GET", "/new", (ctx) => {
+          ctx.res.send("new");
+        }),
+      ).toBe(false);
+    });
+
+    it("should swap all routes at once", () => {
+      app.get("/old", (ctx) => {
+        ctx.res.send("old");
+      });
+
+      const next = router();
+      next.get("/new", (ctx) => {
+        ctx.res.send("new");
+      });
+
+      expect(() => app.swapRoutes(next)).not.toThrow();
+      expect(app.removeRoute("GET", "/old")).toBe(false);
+      expect(app.removeRoute("GET", "/new")).toBe(true);
+    });
+
+    it("should list registered routes", () => {
+      app.get("/users/:id", (ctx) => {
+        ctx.res.send("user");
+      });
+      app.post("/users", (ctx) => {
+        ctx.res.send("created");
+      });
+
+      const routes = app.listRoutes();
+      expect(routes.map((route) => `${route.method} ${route.path}`)).toEqual([
+        "POST /users",
+        "GET /users/:id",
+      ]);
+      expect(routes[1].matchitPath).toBe("/users/{id}");
+      expect(app.routeCount()).toEqual({
+        total: 2,
+        staticRoutes: 1,
+        dynamicRoutes: 1,
+      });
+    });
+
+    it("should pass route options to the core", () => {
+      const limited = server({ maxRequestSize: 1024 });
+      limited.post(
+        "/uploads",
+        [routeOptions({ maxRequestSize: 10, timeout: 500 })],
+        (ctx) => {
+          ctx.res.send("stored");
+        },
+      );
+      limited.post("/comments", (ctx) => {
+        ctx.res.send("posted");
+      });
+
+      const routes = limited.listRoutes();
+      const uploads = routes.find((r) => r.path === "/uploads");
+      expect(uploads?.maxRequestSize).toBe(10);
+      expect(uploads?.timeout).toBe(500);
+      expect(uploads?.caseInsensitive).toBe(false);
+      expect(
+        routes.find((r) => r.path === "/comments")?.maxRequestSize,
+      ).toBeUndefined();
+      limited.close();
+    });
+
+    it("should register case-insensitive routes", () => {
+      app.get(
+        "/Legacy/:id",
+        [routeOptions({ caseInsensitive: true })],
+        (ctx) => {
+          ctx.res.send("legacy");
+        },
+      );
+
+      expect(app.listRoutes()[0].caseInsensitive).toBe(true);
+      expect(() =>
+        app.get("/legacy/:id", (ctx) => {
+          ctx.res.send("duplicate");
+        }),
+      ).toThrow();
+    });
+
+    it("should keep the current routes when a swap fails", () => {
+      app.get("/kept", (ctx) => {
+        ctx.res.send("kept");
+      });
+
+      const next = router();
+      next.get("/items/:a", (ctx) => {
+        ctx.res.send("a");
+      });
+      next.get("/items/:b", (ctx) => {
+        ctx.res.send("b");
+      });
+
+      expect(() => app.swapRoutes(next)).toThrow();
+      expect(app.removeRoute("GET", "/kept")).toBe(true);
+    });
+  });
+
   describe("Global Middleware", () => {
     it("should register global middleware", () => {
       const globalMw = middleware((_, next) => {
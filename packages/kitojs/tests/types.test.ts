@@ -146,4 +146,13 @@ describe("Type Safety", () => {
       expectTypeOf(ctx).toHaveProperty("cache");
     });
   });
+
+  it("should keep path params as strings", () => {
+    expectTypeOf<Context["req"]["params"]>().toEqualTypeOf<
+      Record<string, string>
+    >();
+    expectTypeOf<Context["req"]["numericParams"]>().toEqualTypeOf<
+      Record<string, number>
+    >();
+  });
 });
@@ -42,6 +42,14 @@ export interface ParsedUrl {
   query: Record<string, string | string[]>;
 }
 
+/** Identity of the client process, for requests received over a Unix socket. */
+export interface PeerCredentials {
+  uid: number;
+  gid: number;
+  /** Not reported on every platform */
+  pid?: number;
+}
+
 export interface KitoRequest {
   get method(): string;
   get url(): string;
@@ -50,6 +58,8 @@ export interface KitoRequest {
 
   get body(): unknown;
   get params(): Record<string, string>;
+  /** Path params declared `:name<int>` or `:name<float>`, as numbers */
+  get numericParams(): Record<string, number>;
   get query(): Record<string, string | string[]>;
   get cookies(): Record<string, string>;
   get pathname(): string;
@@ -58,6 +68,8 @@ export interface KitoRequest {
   get hostname(): string;
   get ip(): string;
   get ips(): string[];
+  /** Client process credentials on Unix socket connections, `null` otherwise */
+  get peerCredentials(): PeerCredentials | null;
   get secure(): boolean;
   get xhr(): boolean;
   get originalUrl(): string;
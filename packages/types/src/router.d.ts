@@ -13,6 +13,11 @@ export interface KitoRouterInstance<TExtensions = {}> {
     router: KitoRouterInstance<TExtensions>,
   ): KitoRouterInstance<TExtensions>;
 
+  host(
+    pattern: string,
+    router: KitoRouterInstance<TExtensions>,
+  ): KitoRouterInstance<TExtensions>;
+
   // biome-ignore lint/complexity/noBannedTypes: ...
   get<TSchema extends SchemaDefinition = {}>(
     path: string,
@@ -15,17 +15,30 @@ export type HttpMethod =
 export interface RouteDefinition<TExtensions = unknown> {
   method: HttpMethod;
   path: string;
+  /** Host pattern the route is bound to; unset routes serve any other host */
+  host?: string;
   middlewares: (MiddlewareDefinition | SchemaDefinition)[];
   handler: RouteHandler<SchemaDefinition, TExtensions>;
 }
 
 export interface MiddlewareDefinition {
-  type: "function" | "schema";
+  type: "function" | "schema" | "options";
   handler?: MiddlewareHandler;
   schema?: SchemaDefinition;
+  options?: RouteOptions;
   global: boolean;
 }
 
+/** Per-route overrides of server options, passed with `routeOptions()` */
+export interface RouteOptions {
+  /** Maximum request body size in bytes (larger bodies get 413) */
+  maxRequestSize?: number;
+  /** Time in milliseconds the handler has to start its response (504 after) */
+  timeout?: number;
+  /** Match the path regardless of ASCII case; params keep the request's casing */
+  caseInsensitive?: boolean;
+}
+
 // biome-ignore lint/complexity/noBannedTypes: ...
 export type RouteChain<TExtensions = {}> = {
   // biome-ignore lint/complexity/noBannedTypes: ...
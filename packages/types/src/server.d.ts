@@ -1,29 +1,266 @@
 import type { MiddlewareHandler, RouteHandler } from "./handlers";
-import type { MiddlewareDefinition, RouteChain } from "./routes";
+import type { HttpMethod, MiddlewareDefinition, RouteChain } from "./routes";
 import type { SchemaDefinition } from "./schema/base";
 import type { KitoRouterInstance } from "./router";
 
+export interface TlsOptions {
+  /** PEM certificate chain, as text or a buffer. Takes precedence over `certPath`. */
+  cert?: string | Buffer;
+  /** PEM private key, as text or a buffer. Takes precedence over `keyPath`. */
+  key?: string | Buffer;
+  certPath?: string;
+  keyPath?: string;
+}
+
+export interface Http1Options {
+  /** Reuse connections for several requests. Default: true */
+  keepAlive?: boolean;
+  /** Keep the connection writable after the client half-closes it. Default: false */
+  halfClose?: boolean;
+  /** Flush pipelined responses together instead of one at a time. Default: false */
+  pipelineFlush?: boolean;
+  /** Maximum number of request headers (431 when exceeded). Default: 100 */
+  maxHeaders?: number;
+  /** Maximum size of the request head in bytes (431 when exceeded). Minimum 8192 */
+  maxHeaderSize?: number;
+}
+
+export interface Http2Options {
+  /** Negotiate HTTP/2 (ALPN on TLS, prior-knowledge on plaintext). Default: true */
+  enabled?: boolean;
+  maxConcurrentStreams?: number;
+  initialStreamWindowSize?: number;
+  initialConnectionWindowSize?: number;
+  /** Interval between keep-alive pings in milliseconds */
+  keepAliveInterval?: number;
+  /** Time to wait for a ping acknowledgement in milliseconds */
+  keepAliveTimeout?: number;
+}
+
+export interface RoutingOptions {
+  /**
+   * `"strict"` treats `/users` and `/users/` as different paths, `"ignore"`
+   * serves either spelling from the route that exists, and `"redirect"`
+   * answers 308 pointing at it. Default: `"strict"`
+   */
+  trailingSlash?: "strict" | "ignore" | "redirect";
+  /** Collapse runs of slashes (`//users` → `/users`). Default: false */
+  mergeSlashes?: boolean;
+  /** Resolve `.` and `..` segments (`/a/./b/../c` → `/a/c`). Default: false */
+  removeDotSegments?: boolean;
+  /**
+   * Percent-decode the path before matching and the params handed to
+   * handlers. `%2F` never splits a segment. Default: false
+   */
+  percentDecode?: boolean;
+  /**
+   * Match routes regardless of ASCII case (`/API/Users` reaches `/api/users`).
+   * Params keep the casing of the request. Default: false
+   */
+  caseInsensitive?: boolean;
+}
+
+export interface CorsOptions {
+  /**
+   * Exact origins, `"*"` for any origin, or patterns such as
+   * `"https://*.example.com"`. Default: any origin
+   */
+  origins?: string[];
+  /** Methods allowed in preflights. Default: the methods routed for the path */
+  methods?: string[];
+  /** Request headers allowed in preflights. Default: whatever the preflight asks for */
+  allowedHeaders?: string[];
+  /** Response headers exposed to the calling script */
+  exposedHeaders?: string[];
+  /**
+   * Send `Access-Control-Allow-Credentials: true`. Requires `origins` listing the
+   * allowed origins, without `"*"`. Default: false
+   */
+  credentials?: boolean;
+  /** Seconds browsers may cache a preflight response */
+  maxAge?: number;
+}
+
+export interface SocketOptions {
+  /** Pending connection queue length. Default: 1024 */
+  backlog?: number;
+  /** Set `TCP_NODELAY` on accepted connections */
+  noDelay?: boolean;
+  /** Idle time in milliseconds before TCP keep-alive probes start */
+  keepAliveTime?: number;
+  /** Time in milliseconds between TCP keep-alive probes */
+  keepAliveInterval?: number;
+  /** Unanswered probes before a connection is dropped */
+  keepAliveRetries?: number;
+  /** For IPv6 hosts: `true` for IPv6 only, `false` for dual-stack */
+  ipv6Only?: boolean;
+  recvBufferSize?: number;
+  sendBufferSize?: number;
+}
+
+/**
+ * Address a server is bound to. TCP listeners report `address`, `family`
+ * and `port`; Unix socket listeners report `path` (`@name` for abstract sockets).
+ */
+export interface ServerAddress {
+  address?: string;
+  family?: "IPv4" | "IPv6";
+  port?: number;
+  path?: string;
+}
+
+export type ListenCallback = (addresses: ServerAddress[]) => void;
+
+/**
+ * A single address to listen on: an inherited socket when `fd` is set, a Unix
+ * socket when `unixSocket` is set, TCP otherwise.
+ */
+export interface ListenerOptions {
+  port?: number;
+  host?: string;
+  /** Unix socket path, or `@name` for a Linux abstract-namespace socket */
+  unixSocket?: string;
+  /** Permission bits for the socket file, e.g. `0o660`. Falls back to the server-wide `unixSocketMode`. */
+  unixSocketMode?: number;
+  /** Group name or gid owning the socket file. Falls back to the server-wide `unixSocketGroup`. */
+  unixSocketGroup?: string;
+  /** Adopt an already-open listening socket (TCP or Unix) instead of binding */
+  fd?: number;
+  /** TLS for this listener. TCP listeners fall back to the server-wide `tls`. */
+  tls?: TlsOptions;
+  /** Read a PROXY protocol header on each connection. Falls back to the server-wide `proxyProtocol`. */
+  proxyProtocol?: boolean;
+}
+
 export interface ServerOptions {
   port?: number;
   host?: string;
+  /** Unix socket path, or `@name` for a Linux abstract-namespace socket */
   unixSocket?: string;
+  /** Permission bits for Unix socket files, e.g. `0o660` */
+  unixSocketMode?: number;
+  /** Group name or gid owning Unix socket files */
+  unixSocketGroup?: string;
+  /** Listen on several addresses at once. Replaces `port`/`host`/`unixSocket` */
+  listeners?: ListenerOptions[];
+  /** Also listen on sockets passed by systemd (`LISTEN_FDS` / `LISTEN_PID`) */
+  socketActivation?: boolean;
   trustProxy?: boolean;
+  /**
+   * Answer 405 with an `Allow` header when a path is only routed for other
+   * methods, instead of 404. Default: true
+   */
+  methodNotAllowed?: boolean;
+  /** Trailing-slash policy and path normalization applied before routing */
+  routing?: RoutingOptions;
+  /**
+   * Answer CORS preflights without calling into JavaScript and add CORS
+   * headers to every response, static ones included.
+   */
+  cors?: CorsOptions;
+  /**
+   * Expect a PROXY protocol (v1 or v2) header on every connection and take the
+   * client address from it. Only enable behind a balancer that always sends one.
+   */
+  proxyProtocol?: boolean;
   maxRequestSize?: number;
+  /** Time in milliseconds a handler has to start responding (504 when exceeded) */
   timeout?: number;
+  /**
+   * Time in milliseconds a client has to send the request headers, and to complete
+   * the PROXY header and TLS handshake before them (default: 30000)
+   */
+  headerReadTimeout?: number;
+  /** Time in milliseconds a client has to send the request body (408 when exceeded) */
+  bodyReadTimeout?: number;
   reusePort?: boolean;
+  socket?: SocketOptions;
+  tls?: TlsOptions;
+  http1?: Http1Options;
+  http2?: Http2Options;
+  /** Close connections idle (no traffic, no request in progress) for this many milliseconds */
+  keepAliveTimeout?: number;
+  /** Close connections gracefully after this many requests */
+  maxRequestsPerConnection?: number;
+  /** Maximum time in milliseconds `shutdown()` waits for connections to drain */
+  shutdownTimeout?: number;
+  /** Maximum number of open connections across all listeners */
+  maxConnections?: number;
+  /** Maximum number of open connections from one client IP (extra ones get 503) */
+  maxConnectionsPerIp?: number;
+  /**
+   * What to do at `maxConnections`: stop accepting until a connection closes
+   * (`"pause"`, default) or accept and answer 503 (`"reject"`)
+   */
+  connectionLimitAction?: "pause" | "reject";
+}
+
+export interface ConnectionStats {
+  /** Open connections admitted by the connection limits */
+  active: number;
+  /** Connections turned away by `maxConnections` or `maxConnectionsPerIp` */
+  rejected: number;
+  /** Distinct client IPs with at least one open connection */
+  clients: number;
+}
+
+export interface RouteInfo {
+  method: HttpMethod;
+  /** Path as registered, e.g. `/users/:id` */
+  path: string;
+  /** Path in the router's own syntax, e.g. `/users/{id}` */
+  matchitPath: string;
+  /** Matched by exact lookup rather than by pattern */
+  isStatic: boolean;
+  /** Whether responses come from the JS handler or are produced by the core alone */
+  strategy: "dynamic" | "full_static" | "param_template";
+  /** Request parts validated by the route schema */
+  schema: ("params" | "query" | "body" | "headers")[];
+  maxRequestSize?: number;
+  timeout?: number;
+  /** Matched regardless of ASCII case */
+  caseInsensitive: boolean;
+  /** Host pattern the route is bound to, e.g. `:tenant.example.com` */
+  host?: string;
+}
+
+export interface RouteCount {
+  total: number;
+  staticRoutes: number;
+  dynamicRoutes: number;
 }
 
 // biome-ignore lint/complexity/noBannedTypes: ...
 export interface KitoServerInstance<TExtensions = {}>
   extends KitoRouterInstance<TExtensions> {
-  listen(callback?: () => void): Promise<ServerOptions>;
-  listen(port?: number, callback?: () => void): Promise<ServerOptions>;
+  listen(callback?: ListenCallback): Promise<ServerOptions>;
+  listen(port?: number, callback?: ListenCallback): Promise<ServerOptions>;
   listen(
     port?: number,
     host?: string,
-    callback?: () => void,
+    callback?: ListenCallback,
   ): Promise<ServerOptions>;
-  listen(options: ServerOptions, callback?: () => void): Promise<ServerOptions>;
+  listen(options: ServerOptions, callback?: ListenCallback): Promise<ServerOptions>;
+
+  removeRoute(method: HttpMethod, path: string, host?: string): boolean;
+  replaceRoute(
+    method: HttpMethod,
+    path: string,
+    handler: RouteHandler<SchemaDefinition, TExtensions>,
+  ): boolean;
+  replaceRoute(
+    method: HttpMethod,
+    path: string,
+    middlewares: (MiddlewareDefinition | SchemaDefinition)[],
+    handler: RouteHandler<SchemaDefinition, TExtensions>,
+  ): boolean;
+  swapRoutes(router: KitoRouterInstance<TExtensions>): void;
+  listRoutes(): RouteInfo[];
+  routeCount(): RouteCount;
 
+  addresses(): ServerAddress[];
+  connectionStats(): ConnectionStats;
+  connectionsFrom(ip: string): number;
   close(): void;
+  shutdown(timeout?: number): Promise<void>;
 }